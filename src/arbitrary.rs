@@ -0,0 +1,104 @@
+//! [`Arbitrary`] implementations for the configuration enums, generating
+//! only values Opus accepts.
+//!
+//! [`Arbitrary`]: proptest::arbitrary::Arbitrary
+use crate::{Application, Bandwidth, Bitrate, Channels, FrameDuration, SampleRate, Signal};
+use proptest::{
+    arbitrary::Arbitrary,
+    prelude::{Just, Strategy},
+    prop_oneof,
+    sample::{select, Select},
+    strategy::BoxedStrategy,
+};
+
+/// Implements [`Arbitrary`] for a fieldless enum by picking one of the
+/// listed variants.
+///
+/// [`Arbitrary`]: proptest::arbitrary::Arbitrary
+macro_rules! arbitrary_variants {
+    ($name:ident => [$($variant:ident),+ $(,)?]) => {
+        impl Arbitrary for $name {
+            type Parameters = ();
+            type Strategy = Select<$name>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                select(&[$($name::$variant),+][..])
+            }
+        }
+    };
+}
+
+arbitrary_variants!(Signal => [Auto, Voice, Music]);
+arbitrary_variants!(Application => [Voip, Audio, LowDelay]);
+arbitrary_variants!(Channels => [Auto, Mono, Stereo]);
+arbitrary_variants!(Bandwidth => [
+    Auto,
+    Narrowband,
+    Mediumband,
+    Wideband,
+    Superwideband,
+    Fullband,
+]);
+arbitrary_variants!(FrameDuration => [
+    Arg, Ms2_5, Ms5, Ms10, Ms20, Ms40, Ms60, Ms80, Ms100, Ms120,
+]);
+
+impl Arbitrary for SampleRate {
+    type Parameters = ();
+    type Strategy = Select<SampleRate>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        select(SampleRate::all())
+    }
+}
+
+/// Explicit bitrates are generated within Opus' meaningful range of 500 to
+/// 512000 bits per second.
+impl Arbitrary for Bitrate {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Bitrate>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(Bitrate::Auto),
+            Just(Bitrate::Max),
+            (Bitrate::MIN_BITS_PER_SECOND..=Bitrate::MAX_BITS_PER_SECOND)
+                .prop_map(Bitrate::BitsPerSecond),
+        ]
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Application, Bandwidth, Bitrate, Channels, FrameDuration, SampleRate, Signal};
+    use proptest::{prelude::any, prop_assert_eq, proptest};
+    use std::convert::TryFrom;
+
+    proptest! {
+        #[test]
+        fn round_trip_through_i32(
+            signal in any::<Signal>(),
+            bitrate in any::<Bitrate>(),
+            sample_rate in any::<SampleRate>(),
+            application in any::<Application>(),
+            channels in any::<Channels>(),
+            bandwidth in any::<Bandwidth>(),
+            frame_duration in any::<FrameDuration>(),
+        ) {
+            prop_assert_eq!(Signal::try_from(i32::from(signal)).unwrap(), signal);
+            prop_assert_eq!(Bitrate::try_from(i32::from(bitrate)).unwrap(), bitrate);
+            prop_assert_eq!(SampleRate::try_from(i32::from(sample_rate)).unwrap(), sample_rate);
+            prop_assert_eq!(
+                Application::try_from(i32::from(application)).unwrap(),
+                application
+            );
+            prop_assert_eq!(Channels::try_from(i32::from(channels)).unwrap(), channels);
+            prop_assert_eq!(Bandwidth::try_from(i32::from(bandwidth)).unwrap(), bandwidth);
+            prop_assert_eq!(
+                FrameDuration::try_from(i32::from(frame_duration)).unwrap(),
+                frame_duration
+            );
+        }
+    }
+}
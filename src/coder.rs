@@ -1,12 +1,24 @@
-use crate::{Error, SampleRate};
+use crate::{ffi, Application, Bandwidth, Channels, Error, FrameDuration, SampleRate};
+use std::{convert::TryFrom, time::Duration};
 
 pub use self::{
+    codec::Codec,
     decoder::{size, Decoder},
-    encoder::Encoder,
+    encoder::{size as encoder_size, Encoder, EncoderBuilder, EncoderSettings, Warning},
+    pool::{DecoderKey, DecoderPool, EncoderKey, EncoderPool, Pool, Pooled},
+    reset_group::ResetGroup,
 };
 
+mod codec;
+#[cfg(feature = "ctl-trace")]
+mod ctl_trace;
 mod decoder;
 mod encoder;
+#[cfg(test)]
+mod golden;
+pub mod multistream;
+mod pool;
+mod reset_group;
 
 /// A set of methods that both `Encoder` and `Decoder` have implemented.
 ///
@@ -16,12 +28,447 @@ mod encoder;
 ///
 /// [`Decoder`]: decoder/struct.Decoder.html
 pub trait GenericCtl {
+    /// Issues a CTL get-`request` to Opus and returns the requested value.
+    fn ctl_get(&self, request: i32) -> Result<i32, Error>;
+
+    /// Issues a CTL set-`request` to Opus, setting the requested setting to
+    /// `value`.
+    fn ctl_set(&mut self, request: i32, value: i32) -> Result<(), Error>;
+
     fn final_range(&self) -> Result<u32, Error>;
 
-    fn phase_inversion_disabled(&self) -> Result<bool, Error>;
-    fn set_phase_inversion_disabled(&mut self, disabled: bool) -> Result<(), Error>;
+    /// Gets the configured phase inversion status.
+    fn phase_inversion_disabled(&self) -> Result<bool, Error> {
+        self.ctl_get(ffi::OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST)
+            .map(|b| b == 1)
+    }
+
+    /// If set to `true`, disables the use of phase inversion for intensity
+    /// stereo, improving the quality of mono downmixes, but slightly reducing
+    /// normal stereo quality.
+    ///
+    /// Disabling phase inversion in the decoder does not comply with RFC 6716,
+    /// although it does not cause any interoperability issue and is expected
+    /// to become part of the Opus standard once RFC 6716 is updated by
+    /// draft-ietf-codec-opus-update.
+    fn set_phase_inversion_disabled(&mut self, disabled: bool) -> Result<(), Error> {
+        self.ctl_set(
+            ffi::OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST,
+            i32::from(disabled),
+        )
+    }
 
     fn sample_rate(&self) -> Result<SampleRate, Error>;
 
     fn reset_state(&mut self) -> Result<(), Error>;
 }
+
+/// Opus' documented maximum size of a single frame in bytes.
+const MAX_FRAME_BYTES: usize = 1275;
+
+/// Returns the maximum size in bytes a packet of `frames` frames can take,
+/// independent of bitrate, sample rate, and channels.
+///
+/// This is Opus' maximum frame size per frame plus the bytes framing them:
+/// the table-of-contents byte and, for multiple frames, a frame count byte
+/// and up to two length bytes per frame but the last.
+///
+/// Use this to allocate output buffers that fit the worst case.
+#[must_use]
+pub fn max_packet_bytes(frames: u8) -> usize {
+    let frames = usize::from(frames);
+
+    match frames {
+        0 => 0,
+        1 => 1 + MAX_FRAME_BYTES,
+        _ => 2 + 2 * (frames - 1) + MAX_FRAME_BYTES * frames,
+    }
+}
+
+/// Suggests the widest bandwidth `bitrate_bps` bits per second can carry
+/// with good quality for `channels` channels.
+///
+/// **Info**:
+/// This is heuristic guidance following Opus' recommended bitrates per
+/// channel, e.g. below 12 kbit/s narrowband and from 32 kbit/s fullband.
+/// Opus itself picks the bandwidth per frame depending on the signal, use
+/// [`Bandwidth::Auto`] to leave the choice to Opus.
+///
+/// As the actual channel count is unknown, [`Channels::Auto`] is treated
+/// like stereo, the more demanding case.
+///
+/// [`Bandwidth::Auto`]: ../enum.Bandwidth.html#variant.Auto
+/// [`Channels::Auto`]: ../enum.Channels.html#variant.Auto
+#[must_use]
+pub fn recommended_bandwidth(bitrate_bps: u32, channels: Channels) -> Bandwidth {
+    let bitrate_per_channel = match channels {
+        Channels::Mono => bitrate_bps,
+        Channels::Stereo | Channels::Auto => bitrate_bps / 2,
+    };
+
+    match bitrate_per_channel {
+        0..=11_999 => Bandwidth::Narrowband,
+        12_000..=14_999 => Bandwidth::Mediumband,
+        15_000..=19_999 => Bandwidth::Wideband,
+        20_000..=31_999 => Bandwidth::Superwideband,
+        _ => Bandwidth::Fullband,
+    }
+}
+
+/// Estimates how many packets of `frame` duration it takes to encode
+/// `total`, rounding up as the last packet may be partially filled.
+///
+/// Together with [`max_packet_bytes`] this allows pre-allocating the
+/// packets of a whole recording.
+///
+/// **Info**:
+/// [`FrameDuration::Arg`] has no fixed duration, it yields `0`.
+///
+/// [`max_packet_bytes`]: fn.max_packet_bytes.html
+/// [`FrameDuration::Arg`]: ../enum.FrameDuration.html#variant.Arg
+#[must_use]
+pub fn packet_count(total: Duration, frame: FrameDuration) -> usize {
+    let frame_micros = match frame_micros(frame) {
+        Some(frame_micros) => u128::from(frame_micros),
+        None => return 0,
+    };
+
+    let total_micros = total.as_micros();
+    let full_packets = total_micros / frame_micros;
+    let count = if full_packets * frame_micros < total_micros {
+        full_packets + 1
+    } else {
+        full_packets
+    };
+
+    usize::try_from(count).unwrap_or(usize::MAX)
+}
+
+/// Calculates the average bitrate in bits per second of `total_bytes` of
+/// packets spanning `duration`, e.g. to verify VBR stayed near the target.
+///
+/// A zero `duration` yields `0`, bitrates exceeding `u32::MAX` saturate.
+#[must_use]
+pub fn measured_bitrate(total_bytes: usize, duration: Duration) -> u32 {
+    let micros = duration.as_micros();
+
+    if micros == 0 {
+        return 0;
+    }
+
+    let bits = total_bytes as u128 * 8;
+
+    u32::try_from(bits * 1_000_000 / micros).unwrap_or(u32::MAX)
+}
+
+/// Calculates the amount of interleaved samples a signal of `duration` at
+/// `sample_rate` with `channels` channels consists of, i.e. the length an
+/// input buffer for [`Encoder::encode`] must have.
+///
+/// **Errors**:
+/// Returns [Error::InvalidFrameDuration] for [`FrameDuration::Arg`] and
+/// [Error::InvalidChannels] for [`Channels::Auto`], neither specifies
+/// a size.
+///
+/// [`Encoder::encode`]: struct.Encoder.html#method.encode
+/// [`FrameDuration::Arg`]: ../enum.FrameDuration.html#variant.Arg
+/// [`Channels::Auto`]: ../enum.Channels.html#variant.Auto
+/// [Error::InvalidFrameDuration]: crate::error::Error::InvalidFrameDuration
+/// [Error::InvalidChannels]: crate::error::Error::InvalidChannels
+pub fn frame_size(
+    sample_rate: SampleRate,
+    channels: Channels,
+    duration: FrameDuration,
+) -> Result<usize, Error> {
+    let frame_micros =
+        frame_micros(duration).ok_or_else(|| Error::InvalidFrameDuration(duration.into()))?;
+
+    if channels == Channels::Auto {
+        return Err(Error::InvalidChannels(channels as i32));
+    }
+
+    // Every sample rate is a multiple of 400Hz, the shortest frame of 2.5ms
+    // therefore always spans whole samples.
+    let samples_per_channel = sample_rate as usize * frame_micros as usize / 1_000_000;
+
+    Ok(samples_per_channel * channels as usize)
+}
+
+/// Gets the duration of a `frame` in microseconds, `None` for
+/// [`FrameDuration::Arg`].
+///
+/// [`FrameDuration::Arg`]: ../enum.FrameDuration.html#variant.Arg
+fn frame_micros(frame: FrameDuration) -> Option<u32> {
+    Some(match frame {
+        FrameDuration::Arg => return None,
+        FrameDuration::Ms2_5 => 2_500,
+        FrameDuration::Ms5 => 5_000,
+        FrameDuration::Ms10 => 10_000,
+        FrameDuration::Ms20 => 20_000,
+        FrameDuration::Ms40 => 40_000,
+        FrameDuration::Ms60 => 60_000,
+        FrameDuration::Ms80 => 80_000,
+        FrameDuration::Ms100 => 100_000,
+        FrameDuration::Ms120 => 120_000,
+    })
+}
+
+/// Checks whether `decoder` can decode the output of `encoder`, i.e. both
+/// use the same sample rate and channels.
+///
+/// Opus decodes packets of mismatching configurations without complaint,
+/// but the signal comes out resampled or up- and downmixed.
+///
+/// **Errors**:
+/// Returns [Error::SampleRateMismatch] or [Error::ChannelsMismatch] when the
+/// configurations differ.
+///
+/// Returns [Error::Opus] when Opus failed to report a sample rate.
+///
+/// [Error::SampleRateMismatch]: crate::error::Error::SampleRateMismatch
+/// [Error::ChannelsMismatch]: crate::error::Error::ChannelsMismatch
+/// [Error::Opus]: crate::error::Error::Opus
+pub fn assert_compatible(encoder: &Encoder, decoder: &Decoder) -> Result<(), Error> {
+    let encoder_rate = encoder.sample_rate()?;
+    let decoder_rate = decoder.sample_rate()?;
+
+    if encoder_rate != decoder_rate {
+        return Err(Error::SampleRateMismatch(encoder_rate, decoder_rate));
+    }
+
+    if encoder.channels() != decoder.channels() {
+        return Err(Error::ChannelsMismatch(
+            encoder.channels(),
+            decoder.channels(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Encodes the frame `input` (interleaved if 2 channels) on two encoders
+/// created via [`Encoder::fresh_for_testing`] and asserts both produce
+/// byte-identical packets.
+///
+/// **Panics**:
+/// Panics if the packets differ or Opus fails to create an encoder or to
+/// encode `input`.
+///
+/// [`Encoder::fresh_for_testing`]: struct.Encoder.html#method.fresh_for_testing
+pub fn assert_deterministic(
+    sample_rate: SampleRate,
+    channels: Channels,
+    mode: Application,
+    input: &[i16],
+) {
+    let encode = || {
+        let encoder = Encoder::fresh_for_testing(sample_rate, channels, mode)
+            .expect("Could not create encoder.");
+
+        let mut packet = vec![0; 4000];
+        let len = encoder
+            .encode(input, &mut packet)
+            .expect("Could not encode input.");
+        packet.truncate(len);
+
+        (
+            packet,
+            encoder.final_range().expect("Could not get final range."),
+        )
+    };
+
+    assert_eq!(
+        encode(),
+        encode(),
+        "Encoding the same input on fresh encoders diverged."
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        assert_compatible, assert_deterministic, frame_size, max_packet_bytes, measured_bitrate,
+        packet_count, recommended_bandwidth, Decoder, Encoder, GenericCtl,
+    };
+    use crate::{ffi, Application, Bandwidth, Bitrate, Channels, Error, FrameDuration, SampleRate};
+    use matches::assert_matches;
+    use std::time::Duration;
+
+    #[test]
+    fn raw_ctl_through_generic_ctl() {
+        fn raw_sample_rate<C: GenericCtl>(coder: &C) -> i32 {
+            coder.ctl_get(ffi::OPUS_GET_SAMPLE_RATE_REQUEST).unwrap()
+        }
+
+        let encoder =
+            Encoder::new(SampleRate::Hz24000, Channels::Mono, Application::Audio).unwrap();
+        let mut decoder = Decoder::new(SampleRate::Hz16000, Channels::Mono).unwrap();
+
+        assert_eq!(raw_sample_rate(&encoder), 24000);
+        assert_eq!(raw_sample_rate(&decoder), 16000);
+
+        decoder
+            .ctl_set(ffi::OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST, 1)
+            .unwrap();
+        assert_matches!(decoder.phase_inversion_disabled(), Ok(true));
+    }
+
+    #[test]
+    fn encoding_is_deterministic() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let input = (0..STEREO_20MS)
+            .map(|i| ((i * 7919) % 20000) as i16 - 10000)
+            .collect::<Vec<_>>();
+
+        assert_deterministic(
+            SampleRate::Hz48000,
+            Channels::Stereo,
+            Application::Audio,
+            &input,
+        );
+        assert_deterministic(
+            SampleRate::Hz16000,
+            Channels::Mono,
+            Application::Voip,
+            &input,
+        );
+    }
+
+    fn set_and_get_phase_inversion_disabled(mut coder: impl GenericCtl) {
+        assert_matches!(coder.phase_inversion_disabled(), Ok(false));
+
+        coder.set_phase_inversion_disabled(true).unwrap();
+        assert_matches!(coder.phase_inversion_disabled(), Ok(true));
+
+        coder.set_phase_inversion_disabled(false).unwrap();
+        assert_matches!(coder.phase_inversion_disabled(), Ok(false));
+    }
+
+    #[test]
+    fn phase_inversion_disabled() {
+        set_and_get_phase_inversion_disabled(
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap(),
+        );
+        set_and_get_phase_inversion_disabled(
+            Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap(),
+        );
+    }
+
+    #[test]
+    fn compatible_coders() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        let matching = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        assert_matches!(assert_compatible(&encoder, &matching), Ok(()));
+
+        let other_rate = Decoder::new(SampleRate::Hz16000, Channels::Stereo).unwrap();
+        assert_matches!(
+            assert_compatible(&encoder, &other_rate),
+            Err(Error::SampleRateMismatch(
+                SampleRate::Hz48000,
+                SampleRate::Hz16000
+            ))
+        );
+
+        let other_channels = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        assert_matches!(
+            assert_compatible(&encoder, &other_channels),
+            Err(Error::ChannelsMismatch(Channels::Stereo, Channels::Mono))
+        );
+    }
+
+    #[test]
+    fn max_bitrate_fits_max_packet_bytes() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        encoder.set_bitrate(Bitrate::Max).unwrap();
+
+        let input = (0..STEREO_20MS)
+            .map(|i| (((i * 7919) % 60000) as i32 - 30000) as i16)
+            .collect::<Vec<_>>();
+        let mut packet = vec![0; 4000];
+
+        for _ in 0..5 {
+            let len = encoder.encode(&input, &mut packet).unwrap();
+            assert!(len <= max_packet_bytes(1));
+        }
+    }
+
+    #[test]
+    fn frame_size_of_durations() {
+        assert_matches!(
+            frame_size(SampleRate::Hz8000, Channels::Mono, FrameDuration::Ms2_5),
+            Ok(20)
+        );
+        assert_matches!(
+            frame_size(SampleRate::Hz48000, Channels::Stereo, FrameDuration::Ms120),
+            Ok(11520)
+        );
+        assert_matches!(
+            frame_size(SampleRate::Hz12000, Channels::Stereo, FrameDuration::Ms20),
+            Ok(480)
+        );
+        assert_matches!(
+            frame_size(SampleRate::Hz48000, Channels::Mono, FrameDuration::Arg),
+            Err(Error::InvalidFrameDuration(_))
+        );
+        assert_matches!(
+            frame_size(SampleRate::Hz48000, Channels::Auto, FrameDuration::Ms20),
+            Err(Error::InvalidChannels(_))
+        );
+    }
+
+    #[test]
+    fn measured_bitrate_of_stream() {
+        assert_eq!(measured_bitrate(240_000, Duration::from_secs(20)), 96000);
+        assert_eq!(measured_bitrate(60, Duration::from_millis(20)), 24000);
+        assert_eq!(measured_bitrate(1500, Duration::from_secs(0)), 0);
+        assert_eq!(
+            measured_bitrate(usize::MAX, Duration::from_micros(1)),
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn packet_count_rounds_up() {
+        assert_eq!(
+            packet_count(Duration::from_secs(1), FrameDuration::Ms20),
+            50
+        );
+        assert_eq!(
+            packet_count(Duration::from_millis(1001), FrameDuration::Ms20),
+            51
+        );
+        assert_eq!(
+            packet_count(Duration::from_millis(5), FrameDuration::Ms2_5),
+            2
+        );
+        assert_eq!(packet_count(Duration::from_secs(0), FrameDuration::Ms60), 0);
+        assert_eq!(packet_count(Duration::from_secs(1), FrameDuration::Arg), 0);
+    }
+
+    #[test]
+    fn recommended_bandwidth_by_bitrate() {
+        let cases = [
+            (6_000, Channels::Mono, Bandwidth::Narrowband),
+            (12_000, Channels::Mono, Bandwidth::Mediumband),
+            (16_000, Channels::Mono, Bandwidth::Wideband),
+            (24_000, Channels::Mono, Bandwidth::Superwideband),
+            (64_000, Channels::Mono, Bandwidth::Fullband),
+            (16_000, Channels::Stereo, Bandwidth::Narrowband),
+            (48_000, Channels::Stereo, Bandwidth::Superwideband),
+            (48_000, Channels::Auto, Bandwidth::Superwideband),
+            (128_000, Channels::Stereo, Bandwidth::Fullband),
+        ];
+
+        for &(bitrate, channels, bandwidth) in &cases {
+            assert_eq!(recommended_bandwidth(bitrate, channels), bandwidth);
+        }
+    }
+}
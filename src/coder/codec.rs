@@ -0,0 +1,130 @@
+use super::{assert_compatible, Decoder, Encoder};
+use crate::{packet::Packet, Application, Channels, Result, SampleRate, TryFrom, TryInto};
+
+/// `Codec` pairs an [`Encoder`] and a [`Decoder`] sharing the same sample rate
+/// and channels, e.g. to loop audio back through Opus.
+///
+/// [`Encoder`]: struct.Encoder.html
+/// [`Decoder`]: struct.Decoder.html
+#[derive(Debug)]
+pub struct Codec {
+    encoder: Encoder,
+    decoder: Decoder,
+}
+
+impl Codec {
+    /// Creates a new encoder and decoder with matching configuration.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to create either.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn new(sample_rate: SampleRate, channels: Channels, mode: Application) -> Result<Self> {
+        Ok(Self {
+            encoder: Encoder::new(sample_rate, channels, mode)?,
+            decoder: Decoder::new(sample_rate, channels)?,
+        })
+    }
+
+    /// Pairs an existing `encoder` and `decoder`.
+    ///
+    /// **Errors**:
+    /// Returns [Error::SampleRateMismatch] or [Error::ChannelsMismatch] when
+    /// their configurations differ.
+    ///
+    /// [Error::SampleRateMismatch]: crate::error::Error::SampleRateMismatch
+    /// [Error::ChannelsMismatch]: crate::error::Error::ChannelsMismatch
+    pub fn from_parts(encoder: Encoder, decoder: Decoder) -> Result<Self> {
+        assert_compatible(&encoder, &decoder)?;
+
+        Ok(Self { encoder, decoder })
+    }
+
+    pub fn encoder(&self) -> &Encoder {
+        &self.encoder
+    }
+
+    pub fn encoder_mut(&mut self) -> &mut Encoder {
+        &mut self.encoder
+    }
+
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    pub fn decoder_mut(&mut self) -> &mut Decoder {
+        &mut self.decoder
+    }
+
+    /// Encodes the `pcm` signal (interleaved if 2 channels) as one Opus frame
+    /// and decodes it right away, returning the decoded signal.
+    ///
+    /// **Info**:
+    /// The output is delayed by the codec's lookahead, hence comparing it
+    /// sample by sample with the input will not match.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to encode or decode the signal.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn round_trip(&mut self, pcm: &[i16]) -> Result<Vec<i16>> {
+        let packet = self.encoder.encode_vec(pcm, None)?;
+
+        let mut output = vec![0; pcm.len()];
+        let samples = self.decoder.decode(
+            Some(Packet::try_from(&packet)?),
+            (&mut output).try_into()?,
+            false,
+        )?;
+        output.truncate(samples * self.decoder.channels() as usize);
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Codec;
+    use crate::{Application, Channels, SampleRate};
+
+    fn rms(signal: &[i16]) -> f64 {
+        let sum = signal
+            .iter()
+            .map(|&sample| f64::from(sample) * f64::from(sample))
+            .sum::<f64>();
+
+        (sum / signal.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn round_trip_sine() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let mut codec =
+            Codec::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        let input = (0..MONO_20MS * 10)
+            .map(|i| {
+                let t = i as f32 / 48000.0;
+                ((2.0 * std::f32::consts::PI * 440.0 * t).sin() * 10000.0) as i16
+            })
+            .collect::<Vec<_>>();
+
+        let mut output = Vec::new();
+
+        for frame in input.chunks(MONO_20MS) {
+            let decoded = codec.round_trip(frame).unwrap();
+            assert_eq!(decoded.len(), MONO_20MS);
+
+            output.extend(decoded);
+        }
+
+        // Skip the first frames, the decoder is still catching up with the
+        // encoder's lookahead.
+        let input_rms = rms(&input[MONO_20MS * 2..]);
+        let output_rms = rms(&output[MONO_20MS * 2..]);
+
+        assert!((input_rms - output_rms).abs() < input_rms * 0.1);
+    }
+}
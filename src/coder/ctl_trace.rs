@@ -0,0 +1,115 @@
+//! Human readable names for Opus' CTL requests, used to trace CTL calls.
+
+use crate::ffi;
+
+macro_rules! request_name {
+    ($request:expr, [$($name:ident),* $(,)?]) => {
+        match $request {
+            $(ffi::$name => stringify!($name),)*
+            _ => "UNKNOWN_REQUEST",
+        }
+    };
+}
+
+/// Returns the name of the `ffi` constant matching `request`.
+pub fn request_name(request: i32) -> &'static str {
+    request_name!(
+        request,
+        [
+            OPUS_SET_APPLICATION_REQUEST,
+            OPUS_GET_APPLICATION_REQUEST,
+            OPUS_SET_BITRATE_REQUEST,
+            OPUS_GET_BITRATE_REQUEST,
+            OPUS_SET_MAX_BANDWIDTH_REQUEST,
+            OPUS_GET_MAX_BANDWIDTH_REQUEST,
+            OPUS_SET_VBR_REQUEST,
+            OPUS_GET_VBR_REQUEST,
+            OPUS_SET_BANDWIDTH_REQUEST,
+            OPUS_GET_BANDWIDTH_REQUEST,
+            OPUS_SET_COMPLEXITY_REQUEST,
+            OPUS_GET_COMPLEXITY_REQUEST,
+            OPUS_SET_INBAND_FEC_REQUEST,
+            OPUS_GET_INBAND_FEC_REQUEST,
+            OPUS_SET_PACKET_LOSS_PERC_REQUEST,
+            OPUS_GET_PACKET_LOSS_PERC_REQUEST,
+            OPUS_SET_DTX_REQUEST,
+            OPUS_GET_DTX_REQUEST,
+            OPUS_SET_VBR_CONSTRAINT_REQUEST,
+            OPUS_GET_VBR_CONSTRAINT_REQUEST,
+            OPUS_SET_FORCE_CHANNELS_REQUEST,
+            OPUS_GET_FORCE_CHANNELS_REQUEST,
+            OPUS_SET_SIGNAL_REQUEST,
+            OPUS_GET_SIGNAL_REQUEST,
+            OPUS_GET_LOOKAHEAD_REQUEST,
+            OPUS_GET_SAMPLE_RATE_REQUEST,
+            OPUS_GET_FINAL_RANGE_REQUEST,
+            OPUS_GET_PITCH_REQUEST,
+            OPUS_SET_GAIN_REQUEST,
+            OPUS_GET_GAIN_REQUEST,
+            OPUS_SET_LSB_DEPTH_REQUEST,
+            OPUS_GET_LSB_DEPTH_REQUEST,
+            OPUS_GET_LAST_PACKET_DURATION_REQUEST,
+            OPUS_SET_EXPERT_FRAME_DURATION_REQUEST,
+            OPUS_GET_EXPERT_FRAME_DURATION_REQUEST,
+            OPUS_SET_PREDICTION_DISABLED_REQUEST,
+            OPUS_GET_PREDICTION_DISABLED_REQUEST,
+            OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST,
+            OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST,
+            OPUS_GET_IN_DTX_REQUEST,
+        ]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::request_name;
+    use crate::{coder::Encoder, ffi, Application, Bitrate, Channels, SampleRate};
+    use log::{Log, Metadata, Record};
+    use std::sync::Mutex;
+
+    struct CapturingLogger {
+        lines: Mutex<Vec<String>>,
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            self.lines.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        lines: Mutex::new(Vec::new()),
+    };
+
+    #[test]
+    fn names_requests() {
+        assert_eq!(
+            request_name(ffi::OPUS_SET_BITRATE_REQUEST),
+            "OPUS_SET_BITRATE_REQUEST"
+        );
+        assert_eq!(request_name(-1), "UNKNOWN_REQUEST");
+    }
+
+    #[test]
+    fn traces_set_bitrate() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        encoder.set_bitrate(Bitrate::BitsPerSecond(64123)).unwrap();
+
+        assert!(LOGGER
+            .lines
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|line| line == "Encoder set OPUS_SET_BITRATE_REQUEST: 64123"));
+    }
+}
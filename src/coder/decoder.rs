@@ -1,15 +1,25 @@
 use super::GenericCtl;
 use crate::{
-    error::try_map_opus_error, ffi, packet::Packet, Channels, ErrorCode, MutSignals, Result,
-    SampleRate,
+    error::{create_error, try_map_opus_error},
+    ffi,
+    packet::Packet,
+    Channels, Error, ErrorCode, MutSignals, Result, SampleRate,
+};
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt::{Debug, Formatter, Result as FmtResult},
 };
-use std::convert::TryFrom;
 
 /// `Decoder` to decode.
-#[derive(Debug)]
 pub struct Decoder {
-    pointer: *mut ffi::OpusDecoder,
+    pub(super) pointer: *mut ffi::OpusDecoder,
     channels: Channels,
+    /// Opus allocated the decoder for these channels, reinitialising it for
+    /// more channels would exceed the allocation.
+    allocated_channels: Channels,
+    samples_decoded: u64,
+    /// Opus keeps the gain across resets, caching it saves querying Opus.
+    gain: i32,
 }
 
 /// The Opus decoder can be sent between threads unless the Opus library
@@ -17,24 +27,39 @@ pub struct Decoder {
 /// parallel.
 unsafe impl Send for Decoder {}
 
+impl Debug for Decoder {
+    /// Besides the fields, the sample rate is queried from Opus, a failed
+    /// query is displayed as `<unavailable>`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut debug = f.debug_struct("Decoder");
+        debug
+            .field("pointer", &self.pointer)
+            .field("channels", &self.channels)
+            .field("allocated_channels", &self.allocated_channels)
+            .field("samples_decoded", &self.samples_decoded)
+            .field("gain", &self.gain);
+
+        match self.sample_rate() {
+            Ok(sample_rate) => debug.field("sample_rate", &sample_rate),
+            Err(_) => debug.field("sample_rate", &format_args!("<unavailable>")),
+        };
+
+        debug.finish()
+    }
+}
+
 impl GenericCtl for Decoder {
-    fn final_range(&self) -> Result<u32> {
-        self.decoder_ctl_request(ffi::OPUS_GET_FINAL_RANGE_REQUEST)
-            .map(|v| v as u32)
+    fn ctl_get(&self, request: i32) -> Result<i32> {
+        self.decoder_ctl_request(request)
     }
 
-    fn phase_inversion_disabled(&self) -> Result<bool> {
-        self.decoder_ctl_request(ffi::OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST)
-            .map(|b| b == 1)
+    fn ctl_set(&mut self, request: i32, value: i32) -> Result<()> {
+        self.set_decoder_ctl_request(request, value)
     }
 
-    fn set_phase_inversion_disabled(&mut self, disabled: bool) -> Result<()> {
-        let disable_phase_inversion = if disabled { 1 } else { 0 };
-        self.set_decoder_ctl_request(
-            ffi::OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST,
-            disable_phase_inversion,
-        )
-        .map(|_| ())
+    fn final_range(&self) -> Result<u32> {
+        self.decoder_ctl_request(ffi::OPUS_GET_FINAL_RANGE_REQUEST)
+            .map(|v| v as u32)
     }
 
     fn sample_rate(&self) -> Result<SampleRate> {
@@ -42,13 +67,37 @@ impl GenericCtl for Decoder {
             .and_then(SampleRate::try_from)
     }
 
+    /// Resets the decoder's state, including the counter of
+    /// [`samples_decoded`].
+    ///
+    /// The gain survives the reset in Opus, so the cached [`gain`] is kept.
+    ///
+    /// [`gain`]: struct.Decoder.html#method.gain
+    /// [`samples_decoded`]: struct.Decoder.html#method.samples_decoded
     fn reset_state(&mut self) -> Result<()> {
-        self.decoder_ctl_request(ffi::OPUS_RESET_STATE).map(|_| ())
+        self.decoder_ctl_request(ffi::OPUS_RESET_STATE)?;
+        self.samples_decoded = 0;
+
+        Ok(())
     }
 }
 
 impl Decoder {
     /// Creates a new Opus decoder.
+    ///
+    /// **Info**:
+    /// Opus operates at 48kHz internally, decoders of a lower `sample_rate`
+    /// resample their output down and drop the frequencies above the rate's
+    /// Nyquist frequency. Create the decoder at 48kHz to retrieve the full
+    /// signal, independent of the sample rate the encoder was created with.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus rejected the configuration, with
+    /// [ErrorCode::AllocFail] when Opus failed to allocate the decoder, even
+    /// if Opus did not report an error code.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [ErrorCode::AllocFail]: crate::error::ErrorCode::AllocFail
     pub fn new(sample_rate: SampleRate, channels: Channels) -> Result<Decoder> {
         let mut opus_code = 0;
 
@@ -56,26 +105,99 @@ impl Decoder {
             ffi::opus_decoder_create(sample_rate as i32, channels as i32, &mut opus_code)
         };
 
-        if opus_code == ffi::OPUS_OK || !pointer.is_null() {
-            return Ok(Decoder { pointer, channels });
+        if opus_code == ffi::OPUS_OK && !pointer.is_null() {
+            return Ok(Decoder {
+                pointer,
+                channels,
+                allocated_channels: channels,
+                samples_decoded: 0,
+                gain: 0,
+            });
+        }
+
+        if !pointer.is_null() {
+            unsafe { ffi::opus_decoder_destroy(pointer) }
         }
 
-        Err(ErrorCode::from(opus_code).into())
+        Err(create_error(opus_code))
+    }
+
+    /// Replaces the decoder with a newly created one of the given
+    /// configuration, e.g. to change the channels.
+    ///
+    /// **Errors**:
+    /// Returns the old decoder untouched together with [Error::Opus] when
+    /// Opus failed to create the new decoder.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn reconfigure(
+        self,
+        sample_rate: SampleRate,
+        channels: Channels,
+    ) -> std::result::Result<Decoder, (Decoder, Error)> {
+        match Decoder::new(sample_rate, channels) {
+            Ok(decoder) => Ok(decoder),
+            Err(error) => Err((self, error)),
+        }
+    }
+
+    /// Reinitialises the decoder in place with the given configuration,
+    /// reusing its allocation instead of creating a new decoder.
+    ///
+    /// The decoder is reset to its initial state, including the gain and
+    /// the counter of [`samples_decoded`].
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] with [ErrorCode::BadArgument] when `channels`
+    /// need more memory than the decoder has been created with, e.g. stereo
+    /// on a decoder created as mono, or when Opus rejected the
+    /// configuration. The decoder stays unchanged in either case.
+    ///
+    /// [`samples_decoded`]: struct.Decoder.html#method.samples_decoded
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [ErrorCode::BadArgument]: crate::error::ErrorCode::BadArgument
+    pub fn reinit(&mut self, sample_rate: SampleRate, channels: Channels) -> Result<()> {
+        if size(channels) > size(self.allocated_channels) {
+            return Err(ErrorCode::BadArgument.into());
+        }
+
+        try_map_opus_error(unsafe {
+            ffi::opus_decoder_init(self.pointer, sample_rate as i32, channels as i32)
+        })?;
+
+        self.channels = channels;
+        self.samples_decoded = 0;
+        self.gain = 0;
+
+        Ok(())
     }
 
     /// Decodes an Opus packet as `input` and writes decoded data into `output`.
     /// Passing `None` as `input` indicates a packet loss.
     ///
+    /// On success returns the decoded samples per channel.
+    ///
+    /// **Info**:
+    /// The output always has the decoder's channels, regardless of the
+    /// packet's. Opus duplicates a mono packet into both channels of a
+    /// stereo decoder and downmixes a stereo packet for a mono decoder.
+    ///
     /// **Errors**:
     /// Returns [Error::Opus] when Opus encountered a problem.
     ///
+    /// Returns [Error::MappingExpectedLen] when the length of `output` is not
+    /// a multiple of the decoder's channels.
+    ///
     /// [Error::Opus]: crate::error::Error::Opus
+    /// [Error::MappingExpectedLen]: crate::error::Error::MappingExpectedLen
     pub fn decode(
         &mut self,
         input: Option<Packet<'_>>,
         mut output: MutSignals<'_, i16>,
         fec: bool,
     ) -> Result<usize> {
+        self.check_output_alignment(output.i32_len())?;
+
         let (input_pointer, input_len) = if let Some(value) = input {
             (value.as_ptr(), value.i32_len())
         } else {
@@ -92,7 +214,12 @@ impl Decoder {
                 fec as i32,
             )
         })
-        .map(|n| n as usize)
+        .map(|n| {
+            let samples = n as usize;
+            self.samples_decoded += samples as u64;
+
+            samples
+        })
     }
 
     /// Decodes an Opus frame from floating point input.
@@ -104,13 +231,33 @@ impl Decoder {
     /// **Errors**:
     /// Returns [Error::Opus] when Opus encountered a problem.
     ///
+    /// Returns [Error::MappingExpectedLen] when the length of `output` is not
+    /// a multiple of the decoder's channels.
+    ///
+    /// The sample type is part of the signature, `i16` signals have to be
+    /// decoded via [`decode`] instead:
+    ///
+    /// ```compile_fail
+    /// use audiopus::{coder::Decoder, Channels, MutSignals, SampleRate};
+    /// use std::convert::TryFrom;
+    ///
+    /// let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+    /// let mut output = vec![0_i16; 960];
+    /// let signals = MutSignals::try_from(&mut output).unwrap();
+    /// decoder.decode_float(None, signals, false).unwrap();
+    /// ```
+    ///
+    /// [`decode`]: struct.Decoder.html#method.decode
     /// [Error::Opus]: crate::error::Error::Opus
+    /// [Error::MappingExpectedLen]: crate::error::Error::MappingExpectedLen
     pub fn decode_float(
         &mut self,
         input: Option<Packet<'_>>,
         mut output: MutSignals<'_, f32>,
         fec: bool,
     ) -> Result<usize> {
+        self.check_output_alignment(output.i32_len())?;
+
         let (input_pointer, input_len) = if let Some(value) = input {
             (value.as_ptr(), value.i32_len())
         } else {
@@ -127,7 +274,173 @@ impl Decoder {
                 fec as i32,
             )
         })
-        .map(|n| n as usize)
+        .map(|n| {
+            let samples = n as usize;
+            self.samples_decoded += samples as u64;
+
+            samples
+        })
+    }
+
+    /// Decodes the `input` packet like [`decode`] into a newly allocated
+    /// signal (interleaved if 2 channels), sized by the samples the packet
+    /// contains.
+    ///
+    /// Passing `None` as `input` indicates a packet loss, Opus then conceals
+    /// as many samples as the last decoded packet contained, or 20ms if no
+    /// packet has been decoded yet. Use [`conceal`] to pick the amount of
+    /// samples yourself.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// [`decode`]: struct.Decoder.html#method.decode
+    /// [`conceal`]: struct.Decoder.html#method.conceal
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn decode_vec(&mut self, input: Option<Packet<'_>>, fec: bool) -> Result<Vec<i16>> {
+        // Opus limits packets to 120ms, a 25th of a second is 40ms.
+        let max_samples = self.sample_rate()? as usize / 25 * 3;
+
+        let samples = match input {
+            Some(packet) => self.nb_samples(packet)?,
+            None => match self.last_packet_duration()? as usize {
+                // 20ms are a 50th of a second.
+                0 => self.sample_rate()? as usize / 50,
+                duration => duration,
+            },
+        };
+
+        let channels = self.channels as usize;
+        let mut output = vec![0; samples.min(max_samples) * channels];
+
+        let decoded = self.decode(input, (&mut output).try_into()?, fec)?;
+        output.truncate(decoded * channels);
+
+        Ok(output)
+    }
+
+    /// Conceals a lost packet by letting Opus generate `frame_samples`
+    /// samples per channel of replacement signal (interleaved if 2 channels).
+    ///
+    /// This is the same as passing `None` to [`decode`].
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g.
+    /// `frame_samples` not being a multiple of 2.5ms.
+    ///
+    /// [`decode`]: struct.Decoder.html#method.decode
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn conceal(&mut self, frame_samples: usize) -> Result<Vec<i16>> {
+        let channels = self.channels as usize;
+        let mut output = vec![0; frame_samples * channels];
+
+        let samples = self.decode(None, (&mut output).try_into()?, false)?;
+        output.truncate(samples * channels);
+
+        Ok(output)
+    }
+
+    /// Conceals a lost packet of `ms` milliseconds, the samples per channel
+    /// are derived from the decoder's sample rate.
+    ///
+    /// This is the same as calling [`conceal`] with the duration converted
+    /// to samples.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] with [ErrorCode::BadArgument] when `ms` is not
+    /// one of Opus' frame durations of 5, 10, 20, 40, 60, 80, 100, or 120ms.
+    ///
+    /// [`conceal`]: struct.Decoder.html#method.conceal
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [ErrorCode::BadArgument]: crate::error::ErrorCode::BadArgument
+    pub fn conceal_ms(&mut self, ms: u32) -> Result<Vec<i16>> {
+        match ms {
+            5 | 10 | 20 | 40 | 60 | 80 | 100 | 120 => {}
+            _ => return Err(ErrorCode::BadArgument.into()),
+        }
+
+        let frame_samples = self.sample_rate()? as usize / 1000 * ms as usize;
+
+        self.conceal(frame_samples)
+    }
+
+    /// Decodes all `packets` in order into one contiguous signal
+    /// (interleaved if 2 channels).
+    ///
+    /// The output is allocated once, sized by the sum of samples the
+    /// `packets` contain.
+    ///
+    /// **Errors**:
+    /// Returns [Error::DecodePacket] with the index of the first packet Opus
+    /// failed to process.
+    ///
+    /// [Error::DecodePacket]: crate::error::Error::DecodePacket
+    pub fn decode_all(&mut self, packets: &[Packet<'_>]) -> Result<Vec<i16>> {
+        let channels = self.channels as usize;
+
+        let mut total_samples = 0;
+
+        for (index, packet) in packets.iter().enumerate() {
+            total_samples += self
+                .nb_samples(*packet)
+                .map_err(|error| error_at_packet(error, index))?;
+        }
+
+        let mut output = vec![0; total_samples * channels];
+        let mut decoded_len = 0;
+
+        for (index, packet) in packets.iter().enumerate() {
+            let samples = self
+                .decode(
+                    Some(*packet),
+                    (&mut output[decoded_len..]).try_into()?,
+                    false,
+                )
+                .map_err(|error| error_at_packet(error, index))?;
+
+            decoded_len += samples * channels;
+        }
+
+        output.truncate(decoded_len);
+
+        Ok(output)
+    }
+
+    /// Decodes the `input` packet and returns the signal of each channel
+    /// in its own `Vec`, in channel order.
+    ///
+    /// The packet is decoded interleaved and split afterwards.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn decode_planar(&mut self, input: Packet<'_>) -> Result<Vec<Vec<i16>>> {
+        let channels = self.channels as usize;
+        let mut interleaved = vec![0; self.nb_samples(input)? * channels];
+
+        let samples = self.decode(Some(input), (&mut interleaved).try_into()?, false)?;
+
+        Ok((0..channels)
+            .map(|channel| {
+                interleaved[..samples * channels]
+                    .iter()
+                    .skip(channel)
+                    .step_by(channels)
+                    .copied()
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Opus expects the output to hold a whole number of samples per channel,
+    /// dividing a misaligned length would silently under-report the capacity.
+    fn check_output_alignment(&self, output_len: i32) -> Result<()> {
+        if output_len % self.channels as i32 != 0 {
+            return Err(Error::MappingExpectedLen(self.channels as usize));
+        }
+
+        Ok(())
     }
 
     /// Gets the number of samples of an Opus packet.
@@ -162,6 +475,13 @@ impl Decoder {
 
         try_map_opus_error(ffi_result)?;
 
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "Decoder got {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
         Ok(value)
     }
 
@@ -173,12 +493,34 @@ impl Decoder {
     /// Returns [Error::Opus] when Opus encountered a problem
     ///
     /// [Error::Opus]: crate::error::Error::Opus
-    fn set_decoder_ctl_request(&self, request: i32, value: i32) -> Result<()> {
+    fn set_decoder_ctl_request(&mut self, request: i32, value: i32) -> Result<()> {
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "Decoder set {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
         try_map_opus_error(unsafe { ffi::opus_decoder_ctl(self.pointer, request, value) })?;
 
+        if request == ffi::OPUS_SET_GAIN_REQUEST {
+            self.gain = value;
+        }
+
         Ok(())
     }
 
+    /// Gets the amount of samples per channel the decoder has output since
+    /// its creation or the last [`reset_state`], concealed samples included.
+    ///
+    /// Divided by the sample rate, this is the position in the decoded
+    /// stream.
+    ///
+    /// [`reset_state`]: trait.GenericCtl.html#tymethod.reset_state
+    pub fn samples_decoded(&self) -> u64 {
+        self.samples_decoded
+    }
+
     /// Gets the duration (in samples) of the last packet successfully decoded
     /// or concealed.
     pub fn last_packet_duration(&self) -> Result<u32> {
@@ -198,7 +540,23 @@ impl Decoder {
 
     /// Gets the decoder's configured amount to scale PCM signal by
     /// in Q8 dB units.
+    ///
+    /// The value is cached when setting the gain, use [`gain_live`] to query
+    /// Opus instead.
+    ///
+    /// [`gain_live`]: struct.Decoder.html#method.gain_live
     pub fn gain(&self) -> Result<i32> {
+        Ok(self.gain)
+    }
+
+    /// Queries Opus for the decoder's configured amount to scale PCM signal
+    /// by in Q8 dB units.
+    ///
+    /// This matches [`gain`] unless Opus' state has been altered bypassing
+    /// this decoder.
+    ///
+    /// [`gain`]: struct.Decoder.html#method.gain
+    pub fn gain_live(&self) -> Result<i32> {
         self.decoder_ctl_request(ffi::OPUS_GET_GAIN_REQUEST)
     }
 
@@ -215,14 +573,38 @@ impl Decoder {
     /// **Info**:
     /// This setting survives decoder reset.
     ///
+    /// Changing the gain requires exclusive access, a shared decoder cannot
+    /// be altered:
+    ///
+    /// ```compile_fail
+    /// use audiopus::{coder::Decoder, Channels, SampleRate};
+    ///
+    /// let decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+    /// let shared = &decoder;
+    /// shared.set_gain(256).unwrap();
+    /// ```
+    ///
     /// [`BadArgument`]: ../error/enum.ErrorCode.html#variant.BadArgument
-    pub fn set_gain(&self, gain: i32) -> Result<()> {
+    pub fn set_gain(&mut self, gain: i32) -> Result<()> {
         self.set_decoder_ctl_request(ffi::OPUS_SET_GAIN_REQUEST, gain)
     }
 
+    /// Gets the channels the decoder was created with.
+    pub fn channels(&self) -> Channels {
+        self.channels
+    }
+
     /// Gets size of self's underlying Opus-decoder in bytes.
     pub fn size(&self) -> usize {
-        unsafe { ffi::opus_decoder_get_size(self.channels as i32) as usize }
+        size(self.allocated_channels)
+    }
+}
+
+/// Attaches the `index` of the packet that failed to an Opus error.
+fn error_at_packet(error: Error, index: usize) -> Error {
+    match error {
+        Error::Opus(error_code) => Error::DecodePacket(index, error_code),
+        error => error,
     }
 }
 
@@ -241,13 +623,530 @@ impl Drop for Decoder {
 
 #[cfg(test)]
 mod tests {
-    use super::Decoder;
-    use crate::{Channels, Error, ErrorCode, SampleRate};
+    use super::{Decoder, GenericCtl};
+    use crate::{
+        coder::Encoder, ffi, packet::Packet, pcm, Application, Bitrate, Channels, Error, ErrorCode,
+        MutSignals, SampleRate,
+    };
     use matches::assert_matches;
+    use std::convert::{TryFrom, TryInto};
 
     #[test]
-    fn set_and_get_gain() {
+    fn decoder_construction() {
+        assert_matches!(
+            Decoder::new(SampleRate::Hz48000, Channels::Auto),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+
+        let stereo = Decoder::new(SampleRate::Hz48000, Channels::Stereo)
+            .expect("Could not create stereo decoder");
+        assert!(!stereo.pointer.is_null());
+
+        Decoder::new(SampleRate::Hz8000, Channels::Mono).expect("Could not create mono decoder");
+    }
+
+    #[test]
+    fn debug_queries_configuration() {
+        let decoder = Decoder::new(SampleRate::Hz16000, Channels::Mono).unwrap();
+        let debug = format!("{:?}", decoder);
+
+        assert!(debug.contains("channels: Mono"));
+        assert!(debug.contains("sample_rate: Hz16000"));
+    }
+
+    #[test]
+    fn decode_into_boxed_slice() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let input = pcm::sine(
+            440.0,
+            SampleRate::Hz48000,
+            Channels::Stereo,
+            STEREO_20MS / 2,
+        );
+        let packet_buffer = encoder.encode_vec(&input, None).unwrap();
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut output = vec![0_i16; STEREO_20MS].into_boxed_slice();
+        let samples = decoder
+            .decode(
+                Some(Packet::try_from(&packet_buffer).unwrap()),
+                MutSignals::try_from(&mut output).unwrap(),
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(samples, STEREO_20MS / 2);
+        assert!(output.iter().any(|&sample| sample != 0));
+    }
+
+    #[test]
+    fn decode_misaligned_output() {
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+
+        let mut output = vec![0_i16; 1921];
+        assert_matches!(
+            decoder.decode(None, (&mut output).try_into().unwrap(), false),
+            Err(Error::MappingExpectedLen(2))
+        );
+
+        let mut output = vec![0_f32; 1921];
+        assert_matches!(
+            decoder.decode_float(None, (&mut output).try_into().unwrap(), false),
+            Err(Error::MappingExpectedLen(2))
+        );
+
+        let mut output = vec![0_i16; 1920];
+        assert_matches!(
+            decoder.decode(None, (&mut output).try_into().unwrap(), false),
+            Ok(960)
+        );
+    }
+
+    #[test]
+    fn decode_vec_round_trip() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let input = pcm::sine(
+            440.0,
+            SampleRate::Hz48000,
+            Channels::Stereo,
+            STEREO_20MS / 2,
+        );
+        let packet_buffer = encoder.encode_vec(&input, None).unwrap();
+        let packet = Packet::try_from(&packet_buffer).unwrap();
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let first_concealed = decoder.decode_vec(None, false).unwrap();
+        assert_eq!(first_concealed.len(), STEREO_20MS);
+
+        decoder.reset_state().unwrap();
+        let signal = decoder.decode_vec(Some(packet), false).unwrap();
+        assert_eq!(signal.len(), STEREO_20MS);
+
+        let mut reference_decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut reference = vec![0_i16; STEREO_20MS];
+        reference_decoder
+            .decode(Some(packet), (&mut reference).try_into().unwrap(), false)
+            .unwrap();
+        assert_eq!(signal, reference);
+
+        let concealed = decoder.decode_vec(None, false).unwrap();
+        assert_eq!(concealed.len(), STEREO_20MS);
+    }
+
+    #[test]
+    fn decode_int_matches_decode_float() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let input = pcm::sine(
+            440.0,
+            SampleRate::Hz48000,
+            Channels::Stereo,
+            STEREO_20MS / 2,
+        );
+        let mut packet_buffer = [0; 1500];
+        let len = encoder.encode(&input, &mut packet_buffer).unwrap();
+        let packet = Packet::try_from(&packet_buffer[..len]).unwrap();
+
+        let mut int_decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut int_output = vec![0_i16; STEREO_20MS];
+        int_decoder
+            .decode(Some(packet), (&mut int_output).try_into().unwrap(), false)
+            .unwrap();
+
+        let mut float_decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut float_output = vec![0_f32; STEREO_20MS];
+        float_decoder
+            .decode_float(Some(packet), (&mut float_output).try_into().unwrap(), false)
+            .unwrap();
+
+        assert!(int_output.iter().any(|&sample| sample != 0));
+        assert!(int_output
+            .iter()
+            .zip(&float_output)
+            .all(|(&int, &float)| (f32::from(int) / 32768.0 - float).abs() <= 1.0 / 32768.0));
+    }
+
+    #[test]
+    fn decode_fec_recovers_lost_frame() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip).unwrap();
+        encoder.set_inband_fec(true).unwrap();
+        encoder.set_packet_loss_perc(20).unwrap();
+        encoder.set_bitrate(Bitrate::BitsPerSecond(32000)).unwrap();
+
+        let packets = (0..10)
+            .map(|frame| {
+                let input = (0..MONO_20MS)
+                    .map(|i| {
+                        let t = (frame * MONO_20MS + i) as f32 / 48000.0;
+                        ((2.0 * std::f32::consts::PI * 300.0 * t).sin() * 10000.0) as i16
+                    })
+                    .collect::<Vec<_>>();
+                let mut output = vec![0; 1500];
+                let len = encoder.encode(&input, &mut output).unwrap();
+                output.truncate(len);
+
+                output
+            })
+            .collect::<Vec<_>>();
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        let mut output = vec![0_i16; MONO_20MS];
+
+        for packet in &packets[..5] {
+            decoder
+                .decode(
+                    Some(Packet::try_from(packet).unwrap()),
+                    (&mut output).try_into().unwrap(),
+                    false,
+                )
+                .unwrap();
+        }
+
+        // The sixth packet is lost, its frame is recovered from the seventh.
+        let mut recovered = vec![0_i16; MONO_20MS];
+        let recovered_len = decoder
+            .decode(
+                Some(Packet::try_from(&packets[6]).unwrap()),
+                (&mut recovered).try_into().unwrap(),
+                true,
+            )
+            .unwrap();
+
+        assert_eq!(recovered_len, MONO_20MS);
+        assert!(recovered.iter().any(|&sample| sample != 0));
+
+        let decoded_len = decoder
+            .decode(
+                Some(Packet::try_from(&packets[6]).unwrap()),
+                (&mut output).try_into().unwrap(),
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(decoded_len, MONO_20MS);
+    }
+
+    #[test]
+    fn conceal_decays_energy() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder = Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip).unwrap();
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        let mut output = vec![0_i16; MONO_20MS];
+
+        let tone = pcm::sine(200.0, SampleRate::Hz48000, Channels::Mono, MONO_20MS * 10);
+
+        for input in tone.chunks(MONO_20MS) {
+            let mut packet = vec![0; 1500];
+            let len = encoder.encode(input, &mut packet).unwrap();
+            packet.truncate(len);
+
+            decoder
+                .decode(
+                    Some(Packet::try_from(&packet).unwrap()),
+                    (&mut output).try_into().unwrap(),
+                    false,
+                )
+                .unwrap();
+        }
+
+        let energies = (0..8)
+            .map(|_| {
+                decoder
+                    .conceal(MONO_20MS)
+                    .unwrap()
+                    .iter()
+                    .map(|&sample| f64::from(sample) * f64::from(sample))
+                    .sum::<f64>()
+            })
+            .collect::<Vec<_>>();
+
+        // A sine of amplitude 16384 carries 16384^2 / 2 per sample.
+        let signal_energy = 16384.0 * 16384.0 / 2.0 * MONO_20MS as f64;
+
+        // No sudden cut, the first concealed frame continues the signal.
+        assert!(energies[0] > signal_energy / 2.0);
+
+        // No constant level either, every frame fades further.
+        assert!(energies.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(energies[7] < energies[0] / 10.0);
+    }
+
+    #[test]
+    fn conceal_stereo_frame() {
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+
+        assert_eq!(decoder.conceal(960).unwrap().len(), 1920);
+        assert_eq!(decoder.last_packet_duration().unwrap(), 960);
+
+        assert_matches!(
+            decoder.conceal(100),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn conceal_ms_frame() {
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+
+        assert_eq!(decoder.conceal_ms(20).unwrap().len(), 960 * 2);
+        assert_eq!(decoder.conceal_ms(5).unwrap().len(), 240 * 2);
+
+        let mut narrowband = Decoder::new(SampleRate::Hz8000, Channels::Mono).unwrap();
+        assert_eq!(narrowband.conceal_ms(120).unwrap().len(), 960);
+
+        assert_matches!(
+            decoder.conceal_ms(15),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+        assert_matches!(
+            decoder.conceal_ms(0),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn stereo_decoder_upmixes_mono_packet() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let signal = pcm::sine(440.0, SampleRate::Hz48000, Channels::Mono, MONO_20MS);
+        let packet_buffer = encoder.encode_vec(&signal, None).unwrap();
+        let packet = Packet::try_from(&packet_buffer).unwrap();
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut output = vec![0_i16; MONO_20MS * 2];
+
+        assert_matches!(
+            decoder.decode(Some(packet), (&mut output).try_into().unwrap(), false),
+            Ok(MONO_20MS)
+        );
+        assert!(output.iter().any(|&sample| sample != 0));
+        assert!(output
+            .chunks(2)
+            .all(|stereo_frame| stereo_frame[0] == stereo_frame[1]));
+    }
+
+    #[test]
+    fn decode_all_packets() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let input = [0_i16; STEREO_20MS];
+
+        let buffers = (0..3)
+            .map(|_| {
+                let mut output = vec![0; 256];
+                let len = encoder.encode(&input, &mut output).unwrap();
+                output.truncate(len);
+
+                output
+            })
+            .collect::<Vec<_>>();
+
+        let mut packets = buffers
+            .iter()
+            .map(|buffer| Packet::try_from(buffer).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let signals = decoder.decode_all(&packets).unwrap();
+        assert_eq!(signals.len(), STEREO_20MS * 3);
+
+        // The TOC-byte announces an arbitrary amount of frames (code 3),
+        // but the byte carrying the frame count is missing.
+        let corrupt_packet = vec![0b0000_0011];
+        packets.insert(1, Packet::try_from(&corrupt_packet).unwrap());
+
+        assert_matches!(
+            decoder.decode_all(&packets),
+            Err(Error::DecodePacket(1, ErrorCode::InvalidPacket))
+        );
+    }
+
+    #[test]
+    fn decode_planar_stereo() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let input = [0_i16; STEREO_20MS];
+        let mut packet = vec![0; 256];
+        let len = encoder.encode(&input, &mut packet).unwrap();
+        packet.truncate(len);
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let planes = decoder
+            .decode_planar(Packet::try_from(&packet).unwrap())
+            .unwrap();
+
+        assert_eq!(planes.len(), 2);
+        assert_eq!(planes[0].len(), STEREO_20MS / 2);
+        assert_eq!(planes[1].len(), STEREO_20MS / 2);
+    }
+
+    #[test]
+    fn count_samples_decoded() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let mut packet = vec![0; 256];
+        let len = encoder.encode(&[0_i16; STEREO_20MS], &mut packet).unwrap();
+        packet.truncate(len);
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut output = vec![0_i16; STEREO_20MS];
+
+        for _ in 0..3 {
+            decoder
+                .decode(
+                    Some(Packet::try_from(&packet).unwrap()),
+                    (&mut output).try_into().unwrap(),
+                    false,
+                )
+                .unwrap();
+        }
+
+        // Three frames of 960 samples per channel.
+        assert_eq!(decoder.samples_decoded(), 960 * 3);
+
+        decoder.reset_state().unwrap();
+        assert_eq!(decoder.samples_decoded(), 0);
+    }
+
+    #[test]
+    fn reinit_stereo_to_mono() {
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let allocated_size = decoder.size();
+        decoder.set_gain(256).unwrap();
+        decoder.decode_vec(None, false).unwrap();
+
+        decoder.reinit(SampleRate::Hz16000, Channels::Mono).unwrap();
+
+        assert_eq!(decoder.channels(), Channels::Mono);
+        assert_eq!(decoder.size(), allocated_size);
+        assert_eq!(decoder.samples_decoded(), 0);
+        assert_matches!(decoder.gain(), Ok(0));
+        assert_matches!(decoder.gain_live(), Ok(0));
+        assert_matches!(decoder.sample_rate(), Ok(SampleRate::Hz16000));
+
+        // 20ms of mono at 16kHz.
+        assert_eq!(decoder.decode_vec(None, false).unwrap().len(), 320);
+
+        let mut mono = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        assert_matches!(
+            mono.reinit(SampleRate::Hz48000, Channels::Stereo),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+        assert_eq!(mono.channels(), Channels::Mono);
+    }
+
+    #[test]
+    fn reset_clears_counter_but_keeps_gain() {
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        decoder.set_gain(-512).unwrap();
+        decoder.decode_vec(None, false).unwrap();
+        assert_eq!(decoder.samples_decoded(), 960);
+
+        decoder.reset_state().unwrap();
+
+        assert_eq!(decoder.samples_decoded(), 0);
+        assert_matches!(decoder.gain(), Ok(-512));
+        assert_matches!(decoder.gain_live(), Ok(-512));
+    }
+
+    #[test]
+    fn reconfigure_stereo_to_mono() {
         let decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut decoder = decoder
+            .reconfigure(SampleRate::Hz16000, Channels::Mono)
+            .unwrap();
+        assert_eq!(decoder.channels(), Channels::Mono);
+        assert_eq!(decoder.conceal(320).unwrap().len(), 320);
+
+        let (decoder, error) = decoder
+            .reconfigure(SampleRate::Hz16000, Channels::Auto)
+            .unwrap_err();
+        assert_matches!(error, Error::Opus(ErrorCode::BadArgument));
+        assert_eq!(decoder.channels(), Channels::Mono);
+    }
+
+    #[test]
+    fn decode_at_lower_sample_rate() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz16000, Channels::Mono, Application::Audio).unwrap();
+        let mut packet = vec![0; 256];
+        let len = encoder.encode(&[0; 320], &mut packet).unwrap();
+        packet.truncate(len);
+
+        let decode = |sample_rate| {
+            let mut decoder = Decoder::new(sample_rate, Channels::Mono).unwrap();
+            let mut output = vec![0_i16; MONO_20MS];
+
+            decoder
+                .decode(
+                    Some(Packet::try_from(&packet).unwrap()),
+                    (&mut output).try_into().unwrap(),
+                    false,
+                )
+                .unwrap()
+        };
+
+        assert_eq!(decode(SampleRate::Hz16000), 320);
+        assert_eq!(decode(SampleRate::Hz48000), MONO_20MS);
+    }
+
+    #[test]
+    fn cached_gain_matches_live_gain() {
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        assert_matches!(decoder.gain(), Ok(0));
+        assert_matches!(decoder.gain_live(), Ok(0));
+
+        decoder.set_gain(256).unwrap();
+        assert_matches!(decoder.gain(), Ok(256));
+        assert_matches!(decoder.gain_live(), Ok(256));
+
+        decoder.reset_state().unwrap();
+        assert_matches!(decoder.gain(), Ok(256));
+        assert_matches!(decoder.gain_live(), Ok(256));
+
+        decoder.ctl_set(ffi::OPUS_SET_GAIN_REQUEST, -256).unwrap();
+        assert_matches!(decoder.gain(), Ok(-256));
+        assert_matches!(decoder.gain_live(), Ok(-256));
+
+        assert!(decoder.set_gain(40000).is_err());
+        assert_matches!(decoder.gain(), Ok(-256));
+    }
+
+    #[test]
+    fn set_and_get_gain() {
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
 
         assert_matches!(decoder.gain(), Ok(0));
 
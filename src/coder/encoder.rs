@@ -1,15 +1,333 @@
-use super::GenericCtl;
+use super::{max_packet_bytes, GenericCtl};
 use crate::{
-    error::try_map_opus_error, ffi, Application, Bandwidth, Bitrate, Channels, ErrorCode, Result,
-    SampleRate, Signal, TryFrom,
+    error::{create_error, try_map_opus_error},
+    ffi,
+    packet::{self, MutPacket, Packet},
+    repacketizer::Repacketizer,
+    Application, Bandwidth, Bitrate, Channels, Error, ErrorCode, FrameDuration, Result, SampleRate,
+    Signal, TryFrom,
 };
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+/// How often [`Encoder::encode_within`] retries with a lowered bitrate.
+///
+/// [`Encoder::encode_within`]: struct.Encoder.html#method.encode_within
+const ENCODE_WITHIN_RETRIES: u32 = 3;
+
+/// Configurations Opus accepts but performs poorly with, reported by
+/// [`Encoder::validate`].
+///
+/// [`Encoder::validate`]: struct.Encoder.html#method.validate
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Warning {
+    /// [`Application::LowDelay`] disables the SILK layer, leaving only CELT
+    /// which is suboptimal at the narrowband sample rates of 8kHz and 12kHz.
+    ///
+    /// [`Application::LowDelay`]: ../enum.Application.html#variant.LowDelay
+    LowDelayNarrowband,
+}
+
+/// Encoder settings applied in one go, e.g. via
+/// [`Encoder::new_with_settings`].
+///
+/// Settings left as `None` keep Opus' default, or the encoder's current
+/// value when [`apply`]ing to an existing encoder.
+///
+/// [`Encoder::new_with_settings`]: struct.Encoder.html#method.new_with_settings
+/// [`apply`]: struct.EncoderSettings.html#method.apply
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct EncoderSettings {
+    pub bitrate: Option<Bitrate>,
+    pub complexity: Option<u8>,
+    pub vbr: Option<bool>,
+    pub vbr_constraint: Option<bool>,
+    pub inband_fec: Option<bool>,
+    pub packet_loss_perc: Option<u8>,
+    pub dtx: Option<bool>,
+    pub force_channels: Option<Channels>,
+    pub max_bandwidth: Option<Bandwidth>,
+    pub bandwidth: Option<Bandwidth>,
+    pub signal: Option<Signal>,
+    pub lsb_depth: Option<u8>,
+    pub prediction_disabled: Option<bool>,
+}
+
+impl EncoderSettings {
+    /// Applies all settings that are `Some` to the `encoder`.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus rejected a setting, settings applied
+    /// before the rejected one stay applied.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn apply(&self, encoder: &mut Encoder) -> Result<()> {
+        if let Some(bitrate) = self.bitrate {
+            encoder.set_bitrate(bitrate)?;
+        }
+
+        if let Some(complexity) = self.complexity {
+            encoder.set_complexity(complexity)?;
+        }
+
+        if let Some(vbr) = self.vbr {
+            encoder.set_vbr(vbr)?;
+        }
+
+        if let Some(vbr_constraint) = self.vbr_constraint {
+            encoder.set_vbr_constraint(vbr_constraint)?;
+        }
+
+        if let Some(inband_fec) = self.inband_fec {
+            encoder.set_inband_fec(inband_fec)?;
+        }
+
+        if let Some(packet_loss_perc) = self.packet_loss_perc {
+            encoder.set_packet_loss_perc(packet_loss_perc)?;
+        }
+
+        if let Some(dtx) = self.dtx {
+            encoder.set_dtx(dtx)?;
+        }
+
+        if let Some(force_channels) = self.force_channels {
+            encoder.set_force_channels(force_channels)?;
+        }
+
+        if let Some(max_bandwidth) = self.max_bandwidth {
+            encoder.set_max_bandwidth(max_bandwidth)?;
+        }
+
+        if let Some(bandwidth) = self.bandwidth {
+            encoder.set_bandwidth(bandwidth)?;
+        }
+
+        if let Some(signal) = self.signal {
+            encoder.set_signal(signal)?;
+        }
+
+        if let Some(lsb_depth) = self.lsb_depth {
+            encoder.set_lsb_depth(lsb_depth)?;
+        }
+
+        if let Some(prediction_disabled) = self.prediction_disabled {
+            encoder.set_prediction_disabled(prediction_disabled)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds an [`Encoder`] with all settings applied before its first use.
+///
+/// Settings left unconfigured keep Opus' defaults, the application defaults
+/// to [`Application::Audio`].
+///
+/// [`Encoder`]: struct.Encoder.html
+/// [`Application::Audio`]: ../enum.Application.html#variant.Audio
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct EncoderBuilder {
+    application: Application,
+    settings: EncoderSettings,
+}
+
+impl Default for EncoderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EncoderBuilder {
+    /// Starts a builder without any configured setting.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            application: Application::Audio,
+            settings: EncoderSettings {
+                bitrate: None,
+                complexity: None,
+                vbr: None,
+                vbr_constraint: None,
+                inband_fec: None,
+                packet_loss_perc: None,
+                dtx: None,
+                force_channels: None,
+                max_bandwidth: None,
+                bandwidth: None,
+                signal: None,
+                lsb_depth: None,
+                prediction_disabled: None,
+            },
+        }
+    }
+
+    /// Configures the encoder's intended application.
+    #[must_use]
+    pub const fn application(mut self, application: Application) -> Self {
+        self.application = application;
+
+        self
+    }
+
+    /// Configures the bitrate, see [`Encoder::set_bitrate`].
+    ///
+    /// [`Encoder::set_bitrate`]: struct.Encoder.html#method.set_bitrate
+    #[must_use]
+    pub const fn bitrate(mut self, bitrate: Bitrate) -> Self {
+        self.settings.bitrate = Some(bitrate);
+
+        self
+    }
+
+    /// Configures the complexity, see [`Encoder::set_complexity`].
+    ///
+    /// [`Encoder::set_complexity`]: struct.Encoder.html#method.set_complexity
+    #[must_use]
+    pub const fn complexity(mut self, complexity: u8) -> Self {
+        self.settings.complexity = Some(complexity);
+
+        self
+    }
+
+    /// Configures whether to use variable bitrate, see [`Encoder::set_vbr`].
+    ///
+    /// [`Encoder::set_vbr`]: struct.Encoder.html#method.set_vbr
+    #[must_use]
+    pub const fn vbr(mut self, vbr: bool) -> Self {
+        self.settings.vbr = Some(vbr);
+
+        self
+    }
+
+    /// Configures whether to constrain the variable bitrate, see
+    /// [`Encoder::set_vbr_constraint`].
+    ///
+    /// [`Encoder::set_vbr_constraint`]: struct.Encoder.html#method.set_vbr_constraint
+    #[must_use]
+    pub const fn vbr_constraint(mut self, vbr_constraint: bool) -> Self {
+        self.settings.vbr_constraint = Some(vbr_constraint);
+
+        self
+    }
+
+    /// Configures whether to use inband forward error correction, see
+    /// [`Encoder::set_inband_fec`].
+    ///
+    /// [`Encoder::set_inband_fec`]: struct.Encoder.html#method.set_inband_fec
+    #[must_use]
+    pub const fn inband_fec(mut self, inband_fec: bool) -> Self {
+        self.settings.inband_fec = Some(inband_fec);
+
+        self
+    }
+
+    /// Configures the expected packet loss, see [`Encoder::set_packet_loss_perc`].
+    ///
+    /// [`Encoder::set_packet_loss_perc`]: struct.Encoder.html#method.set_packet_loss_perc
+    #[must_use]
+    pub const fn packet_loss_perc(mut self, packet_loss_perc: u8) -> Self {
+        self.settings.packet_loss_perc = Some(packet_loss_perc);
+
+        self
+    }
+
+    /// Configures whether to use discontinuous transmission, see
+    /// [`Encoder::set_dtx`].
+    ///
+    /// [`Encoder::set_dtx`]: struct.Encoder.html#method.set_dtx
+    #[must_use]
+    pub const fn dtx(mut self, dtx: bool) -> Self {
+        self.settings.dtx = Some(dtx);
+
+        self
+    }
+
+    /// Configures the forced channels, see [`Encoder::set_force_channels`].
+    ///
+    /// [`Encoder::set_force_channels`]: struct.Encoder.html#method.set_force_channels
+    #[must_use]
+    pub const fn force_channels(mut self, force_channels: Channels) -> Self {
+        self.settings.force_channels = Some(force_channels);
+
+        self
+    }
+
+    /// Configures the maximum bandpass, see [`Encoder::set_max_bandwidth`].
+    ///
+    /// [`Encoder::set_max_bandwidth`]: struct.Encoder.html#method.set_max_bandwidth
+    #[must_use]
+    pub const fn max_bandwidth(mut self, max_bandwidth: Bandwidth) -> Self {
+        self.settings.max_bandwidth = Some(max_bandwidth);
+
+        self
+    }
+
+    /// Configures the bandpass, see [`Encoder::set_bandwidth`].
+    ///
+    /// [`Encoder::set_bandwidth`]: struct.Encoder.html#method.set_bandwidth
+    #[must_use]
+    pub const fn bandwidth(mut self, bandwidth: Bandwidth) -> Self {
+        self.settings.bandwidth = Some(bandwidth);
+
+        self
+    }
+
+    /// Configures the type of signal, see [`Encoder::set_signal`].
+    ///
+    /// [`Encoder::set_signal`]: struct.Encoder.html#method.set_signal
+    #[must_use]
+    pub const fn signal(mut self, signal: Signal) -> Self {
+        self.settings.signal = Some(signal);
+
+        self
+    }
+
+    /// Configures the input's depth of signal, see [`Encoder::set_lsb_depth`].
+    ///
+    /// [`Encoder::set_lsb_depth`]: struct.Encoder.html#method.set_lsb_depth
+    #[must_use]
+    pub const fn lsb_depth(mut self, lsb_depth: u8) -> Self {
+        self.settings.lsb_depth = Some(lsb_depth);
+
+        self
+    }
+
+    /// Configures whether to disable prediction, see
+    /// [`Encoder::set_prediction_disabled`].
+    ///
+    /// [`Encoder::set_prediction_disabled`]: struct.Encoder.html#method.set_prediction_disabled
+    #[must_use]
+    pub const fn prediction_disabled(mut self, prediction_disabled: bool) -> Self {
+        self.settings.prediction_disabled = Some(prediction_disabled);
+
+        self
+    }
+
+    /// Gets the settings configured so far.
+    #[must_use]
+    pub const fn settings(&self) -> &EncoderSettings {
+        &self.settings
+    }
+
+    /// Creates the encoder and applies the configured settings in order.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to create the encoder or
+    /// rejected a setting, the first failure is returned.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn build(&self, sample_rate: SampleRate, channels: Channels) -> Result<Encoder> {
+        Encoder::new_with_settings(sample_rate, channels, self.application, &self.settings)
+    }
+}
 
 /// `Encoder` calls to Opus and offers method to encode and issue
 /// requests to Opus.
-#[derive(Debug)]
 pub struct Encoder {
-    pointer: *mut ffi::OpusEncoder,
+    pub(super) pointer: *mut ffi::OpusEncoder,
     channels: Channels,
+    /// Opus allocated the encoder for these channels, reinitialising it for
+    /// more channels would exceed the allocation.
+    allocated_channels: Channels,
 }
 
 /// The Opus encoder can be sent between threads unless the Opus library
@@ -17,7 +335,84 @@ pub struct Encoder {
 /// parallel.
 unsafe impl Send for Encoder {}
 
+impl Debug for Encoder {
+    /// Besides the fields, the sample rate and application are queried from
+    /// Opus, failed queries are displayed as `<unavailable>`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut debug = f.debug_struct("Encoder");
+        debug
+            .field("pointer", &self.pointer)
+            .field("channels", &self.channels)
+            .field("allocated_channels", &self.allocated_channels);
+
+        match self.sample_rate() {
+            Ok(sample_rate) => debug.field("sample_rate", &sample_rate),
+            Err(_) => debug.field("sample_rate", &format_args!("<unavailable>")),
+        };
+
+        match self.application() {
+            Ok(application) => debug.field("application", &application),
+            Err(_) => debug.field("application", &format_args!("<unavailable>")),
+        };
+
+        debug.finish()
+    }
+}
+
+impl Clone for Encoder {
+    /// Forks the encoder including its whole internal state, both encoders
+    /// produce identical packets when fed the same input afterwards.
+    ///
+    /// **Info**:
+    /// Opus keeps the encoder state in one contiguous block without internal
+    /// pointers, the clone is allocated with the same size and the block is
+    /// copied byte by byte.
+    ///
+    /// **Panics**:
+    /// Panics if Opus fails to allocate the new encoder.
+    fn clone(&self) -> Self {
+        let mut opus_code = 0;
+
+        let pointer = unsafe {
+            ffi::opus_encoder_create(
+                SampleRate::Hz48000 as i32,
+                self.allocated_channels as i32,
+                Application::Audio as i32,
+                &mut opus_code,
+            )
+        };
+
+        assert!(
+            opus_code == ffi::OPUS_OK && !pointer.is_null(),
+            "Opus failed to allocate the cloned encoder: {}",
+            create_error(opus_code)
+        );
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                self.pointer.cast::<u8>(),
+                pointer.cast::<u8>(),
+                self.size(),
+            );
+        }
+
+        Encoder {
+            pointer,
+            channels: self.channels,
+            allocated_channels: self.allocated_channels,
+        }
+    }
+}
+
 impl GenericCtl for Encoder {
+    fn ctl_get(&self, request: i32) -> Result<i32> {
+        self.encoder_ctl_request(request)
+    }
+
+    fn ctl_set(&mut self, request: i32, value: i32) -> Result<()> {
+        self.set_encoder_ctl_request(request, value)
+    }
+
     /// Gets the final state of the codec's entropy coder.
     ///
     /// This is used for testing purposes. The encoder state should
@@ -28,29 +423,6 @@ impl GenericCtl for Encoder {
             .map(|v| v as u32)
     }
 
-    /// Gets the encoder's configured phase inversion status.
-    fn phase_inversion_disabled(&self) -> Result<bool> {
-        self.encoder_ctl_request(ffi::OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST)
-            .map(|b| b == 1)
-    }
-
-    /// If set to `true`, disables the use of phase inversion for intensity
-    /// stereo, improving the quality of mono downmixes, but slightly reducing
-    /// normal stereo quality.
-    ///
-    /// Disabling phase inversion in the decoder does not comply with RFC 6716,
-    /// although it does not cause any interoperability issue and is expected
-    /// to become part of the Opus standard once RFC 6716 is updated by
-    /// draft-ietf-codec-opus-update.
-    fn set_phase_inversion_disabled(&mut self, disabled: bool) -> Result<()> {
-        let disable_phase_inversion = if disabled { 1 } else { 0 };
-        self.set_encoder_ctl_request(
-            ffi::OPUS_SET_PHASE_INVERSION_DISABLED_REQUEST,
-            disable_phase_inversion,
-        )
-        .map(|_| ())
-    }
-
     /// Gets the sampling rate the encoder or decoder was initialized with.
     ///
     /// This simply returns the Fs value passed to [`Encoder::new`].
@@ -66,6 +438,11 @@ impl GenericCtl for Encoder {
     /// This should be called when switching streams in order to prevent the
     /// back to back decoding from giving different results from one at a
     /// time decoding.
+    ///
+    /// Only the signal processing state is cleared, settings such as the
+    /// [`application`] stay as they are.
+    ///
+    /// [`application`]: struct.Encoder.html#method.application
     fn reset_state(&mut self) -> Result<()> {
         self.encoder_ctl_request(ffi::OPUS_RESET_STATE).map(|_| ())
     }
@@ -78,8 +455,13 @@ impl Encoder {
     /// If `channels` is set to [`Channels::Auto`] the function will
     /// return [`BadArgument`].
     ///
+    /// **Errors**:
+    /// Returns [`AllocFail`] when Opus failed to allocate the encoder,
+    /// even if Opus did not report an error code.
+    ///
     /// [`Channels::Auto`]: ../enum.Channels.html#variant.Auto
     /// [`BadArgument`]: ../error/enum.ErrorCode.html#variant.BadArgument
+    /// [`AllocFail`]: ../error/enum.ErrorCode.html#variant.AllocFail
     pub fn new(sample_rate: SampleRate, channels: Channels, mode: Application) -> Result<Encoder> {
         let mut opus_code = 0;
 
@@ -92,11 +474,115 @@ impl Encoder {
             )
         };
 
-        if opus_code == ffi::OPUS_OK || !pointer.is_null() {
-            return Ok(Encoder { pointer, channels });
+        if opus_code == ffi::OPUS_OK && !pointer.is_null() {
+            return Ok(Encoder {
+                pointer,
+                channels,
+                allocated_channels: channels,
+            });
+        }
+
+        if !pointer.is_null() {
+            unsafe { ffi::opus_encoder_destroy(pointer) }
+        }
+
+        Err(create_error(opus_code))
+    }
+
+    /// Replaces the encoder with a newly created one of the given
+    /// configuration, e.g. to change the channels.
+    ///
+    /// Settings applied to the old encoder do not carry over.
+    ///
+    /// **Errors**:
+    /// Returns the old encoder untouched together with [Error::Opus] when
+    /// Opus failed to create the new encoder.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn reconfigure(
+        self,
+        sample_rate: SampleRate,
+        channels: Channels,
+        mode: Application,
+    ) -> std::result::Result<Encoder, (Encoder, Error)> {
+        match Encoder::new(sample_rate, channels, mode) {
+            Ok(encoder) => Ok(encoder),
+            Err(error) => Err((self, error)),
+        }
+    }
+
+    /// Reinitialises the encoder in place with the given configuration,
+    /// reusing its allocation instead of creating a new encoder.
+    ///
+    /// The encoder is reset to its initial state, settings applied before
+    /// do not carry over.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] with [ErrorCode::BadArgument] when `channels`
+    /// need more memory than the encoder has been created with, e.g. stereo
+    /// on an encoder created as mono, or when Opus rejected the
+    /// configuration. The encoder stays unchanged in either case.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [ErrorCode::BadArgument]: crate::error::ErrorCode::BadArgument
+    pub fn reinit(
+        &mut self,
+        sample_rate: SampleRate,
+        channels: Channels,
+        mode: Application,
+    ) -> Result<()> {
+        if size(channels) > size(self.allocated_channels) {
+            return Err(ErrorCode::BadArgument.into());
         }
 
-        Err(ErrorCode::from(opus_code).into())
+        try_map_opus_error(unsafe {
+            ffi::opus_encoder_init(
+                self.pointer,
+                sample_rate as i32,
+                channels as i32,
+                mode as i32,
+            )
+        })?;
+
+        self.channels = channels;
+
+        Ok(())
+    }
+
+    /// Creates a new Opus encoder and applies `settings` to it.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to create the encoder or
+    /// rejected a setting.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn new_with_settings(
+        sample_rate: SampleRate,
+        channels: Channels,
+        mode: Application,
+        settings: &EncoderSettings,
+    ) -> Result<Encoder> {
+        let mut encoder = Encoder::new(sample_rate, channels, mode)?;
+        settings.apply(&mut encoder)?;
+
+        Ok(encoder)
+    }
+
+    /// Creates a new Opus encoder in its canonical initial state.
+    ///
+    /// Opus encoding is deterministic, two encoders created by this function
+    /// with the same arguments produce byte-identical output for the same
+    /// input. This is meant for codec regression tests.
+    ///
+    /// See [`coder::assert_deterministic`] for a ready-made check.
+    ///
+    /// [`coder::assert_deterministic`]: fn.assert_deterministic.html
+    pub fn fresh_for_testing(
+        sample_rate: SampleRate,
+        channels: Channels,
+        mode: Application,
+    ) -> Result<Encoder> {
+        Self::new(sample_rate, channels, mode)
     }
 
     /// Issues a CTL get-`request` to Opus.
@@ -113,6 +599,13 @@ impl Encoder {
         let ffi_result = unsafe { ffi::opus_encoder_ctl(self.pointer, request, &mut value) };
         try_map_opus_error(ffi_result)?;
 
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "Encoder got {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
         Ok(value)
     }
 
@@ -126,11 +619,38 @@ impl Encoder {
     ///
     /// [`Encoder`]: struct.Encoder.html
     pub fn set_encoder_ctl_request(&mut self, request: i32, value: i32) -> Result<()> {
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "Encoder set {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
         try_map_opus_error(unsafe { ffi::opus_encoder_ctl(self.pointer, request, value) })?;
 
         Ok(())
     }
 
+    /// Gets the encoder's channels.
+    pub fn channels(&self) -> Channels {
+        self.channels
+    }
+
+    /// Gets the heap memory in bytes Opus allocated for this encoder.
+    ///
+    /// **Info**:
+    /// Opus allocates the whole encoder state in one block on creation and
+    /// does not grow it afterwards, this is meant for budgeting memory on
+    /// constrained targets.
+    pub fn memory_usage(&self) -> usize {
+        self.size()
+    }
+
+    /// Gets size of self's underlying Opus-encoder in bytes.
+    pub fn size(&self) -> usize {
+        size(self.allocated_channels)
+    }
+
     /// Encodes an Opus frame.
     ///
     /// The `input` signal (interleaved if 2 channels) will be encoded into the
@@ -149,6 +669,50 @@ impl Encoder {
         .map(|n| n as usize)
     }
 
+    /// Encodes an Opus frame like [`encode`] and additionally returns the
+    /// RMS level of the `input` signal in dBFS.
+    ///
+    /// The level is relative to the RMS of a square wave at full scale,
+    /// i.e. 32768. A full scale sine therefore measures about -3 dBFS,
+    /// silence measures negative infinity.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// [`encode`]: struct.Encoder.html#method.encode
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_with_level(&self, input: &[i16], output: &mut [u8]) -> Result<(usize, f32)> {
+        let len = self.encode(input, output)?;
+
+        let square_sum = input
+            .iter()
+            .map(|&sample| f64::from(sample) * f64::from(sample))
+            .sum::<f64>();
+        let rms = (square_sum / input.len() as f64).sqrt();
+        let level = 20.0 * (rms / 32768.0).log10();
+
+        Ok((len, level as f32))
+    }
+
+    /// Encodes an Opus frame like [`encode`] and additionally reports whether
+    /// the `input` signal clipped, i.e. any sample sits at `i16::MIN` or
+    /// `i16::MAX`.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// [`encode`]: struct.Encoder.html#method.encode
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_checked_clip(&self, input: &[i16], output: &mut [u8]) -> Result<(usize, bool)> {
+        let len = self.encode(input, output)?;
+
+        let clipped = input
+            .iter()
+            .any(|&sample| sample == i16::MIN || sample == i16::MAX);
+
+        Ok((len, clipped))
+    }
+
     /// Encodes an Opus frame from floating point input.
     ///
     /// The `input` signal (interleaved if 2 channels) will be encoded into the
@@ -167,61 +731,366 @@ impl Encoder {
         .map(|n| n as usize)
     }
 
-    /// Gets the encoder's complexity configuration.
-    pub fn complexity(&self) -> Result<u8> {
-        self.encoder_ctl_request(ffi::OPUS_GET_COMPLEXITY_REQUEST)
-            .map(|v| v as u8)
-    }
-
-    /// Configures the encoder's computational complexity.
+    /// Encodes an Opus frame like [`encode`] into a newly allocated packet of
+    /// at most `max_len` bytes, truncated to the encoded length.
     ///
-    /// **Warning**:
-    /// If `complexity` exceeds 10, [`BadArgument`] will be returned.
+    /// Passing `None` as `max_len` allocates [`max_packet_bytes`] for the
+    /// 20ms frames `input` spans, fitting any packet Opus may produce.
     ///
-    /// [`BadArgument`]: ../error/enum.ErrorCode.html#variant.BadArgument.html
-    pub fn set_complexity(&mut self, complexity: u8) -> Result<()> {
-        self.set_encoder_ctl_request(ffi::OPUS_SET_COMPLEXITY_REQUEST, i32::from(complexity))
-    }
-
-    /// Gets the encoder's configured application.
-    pub fn application(&self) -> Result<Application> {
-        self.encoder_ctl_request(ffi::OPUS_GET_APPLICATION_REQUEST)
-            .and_then(Application::try_from)
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g.
+    /// `max_len` being too small to fit any packet.
+    ///
+    /// [`encode`]: struct.Encoder.html#method.encode
+    /// [`max_packet_bytes`]: fn.max_packet_bytes.html
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_vec(&self, input: &[i16], max_len: Option<usize>) -> Result<Vec<u8>> {
+        let max_len = self.max_len_or_default(max_len, input.len())?;
+        let mut output = vec![0; max_len];
+        let len = self.encode(input, &mut output)?;
+        output.truncate(len);
+
+        Ok(output)
     }
 
-    /// Configures the encoder's intended application.
+    /// Encodes an Opus frame from floating point input like
+    /// [`encode_float`] into a newly allocated packet of at most `max_len`
+    /// bytes, truncated to the encoded length.
     ///
-    /// The initial value is a mandatory argument in the [`new`]-function.
+    /// Passing `None` as `max_len` allocates [`max_packet_bytes`] for the
+    /// 20ms frames `input` spans, fitting any packet Opus may produce.
     ///
-    /// [`new`]: struct.Encoder.html#method.new
-    pub fn set_application(&mut self, application: Application) -> Result<()> {
-        self.set_encoder_ctl_request(ffi::OPUS_SET_APPLICATION_REQUEST, application as i32)
-            .map(|_| ())
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g.
+    /// `max_len` being too small to fit any packet.
+    ///
+    /// [`encode_float`]: struct.Encoder.html#method.encode_float
+    /// [`max_packet_bytes`]: fn.max_packet_bytes.html
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_float_vec(&self, input: &[f32], max_len: Option<usize>) -> Result<Vec<u8>> {
+        let max_len = self.max_len_or_default(max_len, input.len())?;
+        let mut output = vec![0; max_len];
+        let len = self.encode_float(input, &mut output)?;
+        output.truncate(len);
+
+        Ok(output)
     }
 
-    /// Configures the bitrate in the encoder.
+    /// Returns `max_len` or, if `None`, the worst case size of a packet
+    /// encoded from `input_len` interleaved samples.
     ///
-    /// Rates from 500 to 512000 bits per second are meaningful,
-    /// as well as the special values [`Bitrate::Auto`] and [`Bitrate::Max`].
-    /// [`Bitrate::Max`] can be used to cause the codec to use
-    /// as much rate as it can, which is useful for controlling the rate by
-    /// adjusting the output buffer size.
-    pub fn set_bitrate(&mut self, bitrate: Bitrate) -> Result<()> {
-        self.set_encoder_ctl_request(ffi::OPUS_SET_BITRATE_REQUEST, bitrate.into())?;
+    /// Opus splits frames longer than 20ms into multiple frames, one frame
+    /// is assumed per started 20ms.
+    fn max_len_or_default(&self, max_len: Option<usize>, input_len: usize) -> Result<usize> {
+        if let Some(max_len) = max_len {
+            return Ok(max_len);
+        }
 
-        Ok(())
+        let samples_per_channel = input_len / (self.channels as usize).max(1);
+        let samples_per_20ms = self.sample_rate()? as usize / 50;
+        let full_frames = samples_per_channel / samples_per_20ms;
+        let frames = if full_frames * samples_per_20ms < samples_per_channel {
+            full_frames + 1
+        } else {
+            full_frames
+        };
+
+        Ok(max_packet_bytes(
+            u8::try_from(frames.max(1)).unwrap_or(u8::MAX),
+        ))
     }
 
-    /// Gets the encoder's configured bandpass.
-    pub fn bitrate(&self) -> Result<Bitrate> {
-        self.encoder_ctl_request(ffi::OPUS_GET_BITRATE_REQUEST)
-            .and_then(Bitrate::try_from)
+    /// Encodes a silent frame of `frame_samples` samples per channel into a
+    /// packet of at most `max_len` bytes.
+    ///
+    /// Opus encodes silence in a few bytes, this keeps a stream alive
+    /// cheaply, e.g. while a microphone is muted.
+    ///
+    /// **Info**:
+    /// With [`set_dtx`] enabled, Opus sends even less data during prolonged
+    /// silence, the returned packets may then consist of a single byte and
+    /// the decoder generates comfort noise in their place.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g.
+    /// `frame_samples` not being a valid frame size.
+    ///
+    /// [`set_dtx`]: struct.Encoder.html#method.set_dtx
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_silence(&self, frame_samples: usize, max_len: usize) -> Result<Vec<u8>> {
+        let silence = vec![0; frame_samples * self.channels as usize];
+
+        self.encode_vec(&silence, Some(max_len))
     }
 
-    /// Enables variable bitrate (VBR) in the encoder.
+    /// Encodes the frame `input` (interleaved if 2 channels) into a packet of
+    /// at most `max_bytes` bytes, lowering the bitrate for this frame if the
+    /// configured one produces a larger packet.
     ///
-    /// The configured bitrate may not be met exactly because frames must be an
-    /// integer number of bytes in length.
+    /// The frame is first encoded at the configured bitrate. If the packet
+    /// exceeds `max_bytes`, it is encoded again at the bitrate averaging
+    /// `max_bytes` per frame, then at half and a quarter of that bitrate.
+    /// If none of these fit, Opus is handed `max_bytes` as hard limit,
+    /// squeezing the frame into it at the cost of quality.
+    ///
+    /// Every attempt encodes on a [clone] of the encoder, as each encode
+    /// advances the encoder's state and encoding a frame twice would corrupt
+    /// the prediction of the following frames. The encoder adopts the state
+    /// of the attempt that fit, with its bitrate restored to the one
+    /// [`bitrate`] reported before.
+    ///
+    /// **Info**:
+    /// Opus reports an automatic bitrate as the bitrate it currently derives,
+    /// hence an encoder set to [`Bitrate::Auto`] keeps that derived bitrate
+    /// fixed once a retry was needed.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g.
+    /// `max_bytes` being too small to fit any packet. The encoder is left
+    /// unchanged then.
+    ///
+    /// [clone]: struct.Encoder.html#impl-Clone
+    /// [`bitrate`]: struct.Encoder.html#method.bitrate
+    /// [`Bitrate::Auto`]: ../enum.Bitrate.html#variant.Auto
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_within(&mut self, input: &[i16], max_bytes: usize) -> Result<Vec<u8>> {
+        let configured = self.bitrate()?;
+        let fitting = self.fitting_bits_per_second(input.len(), max_bytes)?;
+        let attempts = std::iter::once(configured).chain((0..ENCODE_WITHIN_RETRIES).map(|retry| {
+            Bitrate::BitsPerSecond((fitting >> retry).max(Bitrate::MIN_BITS_PER_SECOND))
+        }));
+
+        for bitrate in attempts {
+            let mut attempt = self.clone();
+            attempt.set_bitrate(bitrate)?;
+            let packet = attempt.encode_vec(input, None)?;
+
+            if packet.len() <= max_bytes {
+                attempt.set_bitrate(configured)?;
+                *self = attempt;
+
+                return Ok(packet);
+            }
+        }
+
+        self.encode_vec(input, Some(max_bytes))
+    }
+
+    /// Returns the bitrate at which a frame of `input_len` samples averages
+    /// `max_bytes` bytes.
+    fn fitting_bits_per_second(&self, input_len: usize, max_bytes: usize) -> Result<i32> {
+        let samples_per_channel = (input_len / (self.channels as usize).max(1)).max(1);
+        let bits_per_second =
+            max_bytes as u64 * 8 * self.sample_rate()? as u64 / samples_per_channel as u64;
+
+        Ok(i32::try_from(bits_per_second)
+            .unwrap_or(i32::MAX)
+            .clamp(Bitrate::MIN_BITS_PER_SECOND, Bitrate::MAX_BITS_PER_SECOND))
+    }
+
+    /// Encodes `frames` consecutive 20ms frames of the `input` signal
+    /// (interleaved if 2 channels) and combines them into one packet,
+    /// e.g. three frames result in a 60ms packet.
+    ///
+    /// Each frame and the combined packet may take up to `max_len` bytes.
+    ///
+    /// **Errors**:
+    /// Returns [Error::SignalsExpectedLen] when `input` does not hold exactly
+    /// `frames` frames of 20ms.
+    ///
+    /// Returns [Error::Opus] when Opus failed to encode or combine the frames,
+    /// e.g. when the combined packet exceeds 120ms.
+    ///
+    /// [Error::SignalsExpectedLen]: crate::error::Error::SignalsExpectedLen
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_multiframe(&self, input: &[i16], frames: u8, max_len: usize) -> Result<Vec<u8>> {
+        // 20ms are a 50th of a second.
+        let frame_len = self.sample_rate()? as usize / 50 * self.channels as usize;
+        let expected_len = frame_len * usize::from(frames);
+
+        if input.len() != expected_len {
+            return Err(Error::SignalsExpectedLen(expected_len));
+        }
+
+        let frame_packets = input
+            .chunks(frame_len)
+            .map(|frame| {
+                let mut packet = vec![0; max_len];
+                let len = self.encode(frame, &mut packet)?;
+                packet.truncate(len);
+
+                Ok(packet)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // The repacketizer points into the frame packets, they outlive it.
+        let repacketizer = Repacketizer::new();
+
+        for packet in &frame_packets {
+            repacketizer.repacketizer_cat(Packet::try_from(packet)?)?;
+        }
+
+        let mut output = vec![0; max_len];
+        let output_packet = MutPacket::try_from(&mut output)?;
+        let max_output_len = output_packet.i32_len()?;
+        let len = repacketizer.repacketizer_out(output_packet, max_output_len)?;
+        output.truncate(len);
+
+        Ok(output)
+    }
+
+    /// Gets the encoder's complexity configuration.
+    pub fn complexity(&self) -> Result<u8> {
+        self.encoder_ctl_request(ffi::OPUS_GET_COMPLEXITY_REQUEST)
+            .map(|v| v as u8)
+    }
+
+    /// Configures the encoder's computational complexity.
+    ///
+    /// **Warning**:
+    /// If `complexity` exceeds 10, [`BadArgument`] will be returned.
+    ///
+    /// [`BadArgument`]: ../error/enum.ErrorCode.html#variant.BadArgument.html
+    pub fn set_complexity(&mut self, complexity: u8) -> Result<()> {
+        self.set_encoder_ctl_request(ffi::OPUS_SET_COMPLEXITY_REQUEST, i32::from(complexity))
+    }
+
+    /// Gets the encoder's configured application.
+    pub fn application(&self) -> Result<Application> {
+        self.encoder_ctl_request(ffi::OPUS_GET_APPLICATION_REQUEST)
+            .and_then(Application::try_from)
+    }
+
+    /// Configures the encoder's intended application.
+    ///
+    /// The initial value is a mandatory argument in the [`new`]-function.
+    ///
+    /// [`new`]: struct.Encoder.html#method.new
+    pub fn set_application(&mut self, application: Application) -> Result<()> {
+        self.set_encoder_ctl_request(ffi::OPUS_SET_APPLICATION_REQUEST, application as i32)
+            .map(|_| ())
+    }
+
+    /// Checks the encoder's configuration for combinations of settings Opus
+    /// accepts but performs poorly with.
+    ///
+    /// An empty list indicates no problems were found.
+    pub fn validate(&self) -> Result<Vec<Warning>> {
+        let mut warnings = Vec::new();
+
+        let narrowband_rate = matches!(
+            self.sample_rate()?,
+            SampleRate::Hz8000 | SampleRate::Hz12000
+        );
+
+        if narrowband_rate && self.application()? == Application::LowDelay {
+            warnings.push(Warning::LowDelayNarrowband);
+        }
+
+        Ok(warnings)
+    }
+
+    /// Configures the bitrate in the encoder.
+    ///
+    /// Rates from 500 to 512000 bits per second are meaningful,
+    /// as well as the special values [`Bitrate::Auto`] and [`Bitrate::Max`].
+    /// [`Bitrate::Max`] can be used to cause the codec to use
+    /// as much rate as it can, which is useful for controlling the rate by
+    /// adjusting the output buffer size.
+    pub fn set_bitrate(&mut self, bitrate: Bitrate) -> Result<()> {
+        self.set_encoder_ctl_request(ffi::OPUS_SET_BITRATE_REQUEST, bitrate.into())?;
+
+        Ok(())
+    }
+
+    /// Configures the bitrate in the encoder as `bps_per_channel` bits per
+    /// second for each of the encoder's channels, e.g. 48000 on a stereo
+    /// encoder sets a total of 96000.
+    ///
+    /// A total exceeding `i32::MAX` saturates, Opus limits the bitrate to
+    /// its maximum in that case.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus rejected the bitrate, e.g. a total
+    /// of zero.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn set_bitrate_per_channel(&mut self, bps_per_channel: u32) -> Result<()> {
+        let total = bps_per_channel.saturating_mul(self.channels as u32);
+        let total = i32::try_from(total).unwrap_or(i32::MAX);
+
+        self.set_encoder_ctl_request(ffi::OPUS_SET_BITRATE_REQUEST, total)
+    }
+
+    /// Moves the encoder's bitrate towards `target` by at most `step_bps`
+    /// bits per second and returns the newly configured bitrate.
+    ///
+    /// Calling this repeatedly, e.g. every few frames, avoids abrupt quality
+    /// jumps when adapting to network conditions.
+    /// The sign of `step_bps` is ignored, the direction is decided by
+    /// `target`.
+    ///
+    /// **Info**:
+    /// [`Bitrate::Auto`] and [`Bitrate::Max`] have no numeric distance to
+    /// ramp over, they are applied immediately.
+    ///
+    /// [`Bitrate::Auto`]: ../enum.Bitrate.html#variant.Auto
+    /// [`Bitrate::Max`]: ../enum.Bitrate.html#variant.Max
+    pub fn ramp_bitrate(&mut self, target: Bitrate, step_bps: i32) -> Result<Bitrate> {
+        let target_bits = match target {
+            Bitrate::BitsPerSecond(bits) => bits,
+            Bitrate::Auto | Bitrate::Max => {
+                self.set_bitrate(target)?;
+
+                return Ok(target);
+            }
+        };
+
+        let current_bits = match self.bitrate()? {
+            Bitrate::BitsPerSecond(bits) => bits,
+            Bitrate::Auto | Bitrate::Max => target_bits,
+        };
+
+        let step = step_bps.saturating_abs();
+        let next_bits = if current_bits < target_bits {
+            current_bits.saturating_add(step).min(target_bits)
+        } else {
+            current_bits.saturating_sub(step).max(target_bits)
+        };
+
+        let next = Bitrate::BitsPerSecond(next_bits);
+        self.set_bitrate(next)?;
+
+        Ok(next)
+    }
+
+    /// Gets the encoder's configured bandpass.
+    pub fn bitrate(&self) -> Result<Bitrate> {
+        self.encoder_ctl_request(ffi::OPUS_GET_BITRATE_REQUEST)
+            .and_then(Bitrate::try_from)
+    }
+
+    /// Configures constrained variable bitrate (CVBR) targeting `target`,
+    /// the mode recommended for streaming.
+    ///
+    /// This enables VBR, enables the VBR constraint, and sets the bitrate,
+    /// in this order.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus rejected a setting, settings applied
+    /// before the rejected one stay applied.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn set_cvbr(&mut self, target: Bitrate) -> Result<()> {
+        self.set_vbr(true)?;
+        self.set_vbr_constraint(true)?;
+        self.set_bitrate(target)
+    }
+
+    /// Enables variable bitrate (VBR) in the encoder.
+    ///
+    /// The configured bitrate may not be met exactly because frames must be an
+    /// integer number of bytes in length.
     ///
     /// **Warning**:
     /// Only the MDCT mode of Opus currently heeds the constraint.
@@ -369,6 +1238,18 @@ impl Encoder {
             .map(|n| n as u32)
     }
 
+    /// Gets the algorithmic delay added by the entire codec in milliseconds.
+    ///
+    /// This is the [`lookahead`] converted via the encoder's sample rate.
+    ///
+    /// [`lookahead`]: struct.Encoder.html#method.lookahead
+    pub fn algorithmic_delay_ms(&self) -> Result<f32> {
+        let lookahead = self.lookahead()?;
+        let sample_rate = self.sample_rate()?;
+
+        Ok(lookahead as f32 * 1000.0 / sample_rate as i32 as f32)
+    }
+
     /// Configures mono/stereo forcing in the encoder.
     ///
     /// This can force the encoder to produce packets encoded as either
@@ -386,6 +1267,24 @@ impl Encoder {
             .and_then(Channels::try_from)
     }
 
+    /// Determines whether the encoder is forced to code in stereo.
+    ///
+    /// This is derived from [`force_channels`] and returns `false` when the
+    /// encoder is forced to mono or allowed to pick mono or stereo coding on
+    /// its own.
+    ///
+    /// **Info**:
+    /// Opus does not expose a stereo width or intensity stereo control.
+    /// The stereo-related settings available are [`set_force_channels`] and
+    /// the phase inversion setting of [`GenericCtl`].
+    ///
+    /// [`force_channels`]: struct.Encoder.html#method.force_channels
+    /// [`set_force_channels`]: struct.Encoder.html#method.set_force_channels
+    /// [`GenericCtl`]: ../trait.GenericCtl.html
+    pub fn stereo_forced(&self) -> Result<bool> {
+        Ok(self.force_channels()? == Channels::Stereo)
+    }
+
     /// Gets the encoder's configured maximum allowed bandpass.
     pub fn max_bandwidth(&self) -> Result<Bandwidth> {
         self.encoder_ctl_request(ffi::OPUS_GET_MAX_BANDWIDTH_REQUEST)
@@ -453,6 +1352,26 @@ impl Encoder {
             .and_then(Bandwidth::try_from)
     }
 
+    /// Gets the bandpass the encoder actually chose for `last_packet`, as
+    /// opposed to [`bandwidth`] which does not follow the encoder's per-frame
+    /// decisions.
+    ///
+    /// Opus does not expose this choice via CTL, it is read from the
+    /// packet's table-of-contents byte instead.
+    ///
+    /// **Errors**:
+    /// Returns [Error::InvalidBandwidth] when Opus failed to read the
+    /// bandpass from the packet.
+    ///
+    /// [`bandwidth`]: struct.Encoder.html#method.bandwidth
+    /// [Error::InvalidBandwidth]: crate::error::Error::InvalidBandwidth
+    // Reading the choice does not need the encoder, the method exists to
+    // sit next to the configured bandwidth.
+    #[allow(clippy::unused_self)]
+    pub fn last_bandwidth(&self, last_packet: Packet<'_>) -> Result<Bandwidth> {
+        packet::bandwidth(last_packet)
+    }
+
     /// Sets the encoder's bandpass to a specific value.
     ///
     /// This prevents the encoder from automatically selecting the bandpass
@@ -481,6 +1400,17 @@ impl Encoder {
             .map(|_| ())
     }
 
+    /// Gets whether the last encoded frame was a discontinuous transmission
+    /// frame, i.e. comfort noise that may not need to be transmitted.
+    ///
+    /// This is always `false` unless DTX is enabled via [`set_dtx`].
+    ///
+    /// [`set_dtx`]: struct.Encoder.html#method.set_dtx
+    pub fn in_dtx(&self) -> Result<bool> {
+        self.encoder_ctl_request(ffi::OPUS_GET_IN_DTX_REQUEST)
+            .map(|n| n == 1)
+    }
+
     /// Enables the encoder's use of discontinuous transmission (DTX).
     pub fn enable_dtx(&mut self) -> Result<()> {
         self.set_dtx(true)
@@ -514,6 +1444,32 @@ impl Encoder {
         self.set_encoder_ctl_request(ffi::OPUS_SET_LSB_DEPTH_REQUEST, i32::from(lsb_depth))
             .map(|_| ())
     }
+
+    /// Gets the encoder's configured frame duration.
+    pub fn expert_frame_duration(&self) -> Result<FrameDuration> {
+        self.encoder_ctl_request(ffi::OPUS_GET_EXPERT_FRAME_DURATION_REQUEST)
+            .and_then(FrameDuration::try_from)
+    }
+
+    /// Configures the encoder's use of variable duration frames.
+    ///
+    /// When set to a duration, the encoder always uses frames of that
+    /// duration, e.g. 2.5ms or 5ms frames for lowest latency.
+    /// The input signal must then hold at least that duration, surplus
+    /// samples are dropped.
+    ///
+    /// The default is [`FrameDuration::Arg`], selecting the frame duration
+    /// from the length of the input signal.
+    ///
+    /// [`FrameDuration::Arg`]: ../enum.FrameDuration.html#variant.Arg
+    pub fn set_expert_frame_duration(&mut self, duration: FrameDuration) -> Result<()> {
+        self.set_encoder_ctl_request(ffi::OPUS_SET_EXPERT_FRAME_DURATION_REQUEST, duration as i32)
+    }
+}
+
+/// Gets size of an Opus-encoder in bytes.
+pub fn size(channels: Channels) -> usize {
+    unsafe { ffi::opus_encoder_get_size(channels as i32) as usize }
 }
 
 impl Drop for Encoder {
@@ -526,9 +1482,17 @@ impl Drop for Encoder {
 
 #[cfg(test)]
 mod tests {
-    use super::Encoder;
-    use crate::{Application, Bandwidth, Bitrate, Channels, Error, ErrorCode, SampleRate, Signal};
+    use super::{
+        max_packet_bytes, size, Encoder, EncoderBuilder, EncoderSettings, GenericCtl, Warning,
+    };
+    use crate::{
+        ffi,
+        packet::{self, Packet},
+        pcm, Application, Bandwidth, Bitrate, Channels, Error, ErrorCode, FrameDuration,
+        SampleRate, Signal,
+    };
     use matches::assert_matches;
+    use std::convert::TryFrom;
 
     #[test]
     fn set_get_inband_fec() {
@@ -558,6 +1522,55 @@ mod tests {
         assert_matches!(encoder.inband_fec(), Ok(false));
     }
 
+    #[test]
+    fn set_get_expert_frame_duration() {
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        assert_matches!(encoder.expert_frame_duration(), Ok(FrameDuration::Arg));
+
+        let durations = [
+            FrameDuration::Ms2_5,
+            FrameDuration::Ms5,
+            FrameDuration::Ms10,
+            FrameDuration::Ms20,
+            FrameDuration::Ms40,
+            FrameDuration::Ms60,
+            FrameDuration::Ms80,
+            FrameDuration::Ms100,
+            FrameDuration::Ms120,
+            FrameDuration::Arg,
+        ];
+
+        for &duration in &durations {
+            encoder
+                .set_expert_frame_duration(duration)
+                .expect("Could not set expert frame duration.");
+            assert_eq!(encoder.expert_frame_duration().unwrap(), duration);
+        }
+    }
+
+    #[test]
+    fn in_dtx() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip).unwrap();
+        encoder.set_dtx(true).unwrap();
+        let mut output = [0; 1500];
+
+        let entered_dtx = (0..50).any(|_| {
+            encoder.encode(&[0; MONO_20MS], &mut output).unwrap();
+            encoder.in_dtx().unwrap()
+        });
+        assert!(entered_dtx);
+
+        let tone = music_like_signal(MONO_20MS);
+        encoder.encode(&tone, &mut output).unwrap();
+        assert_matches!(encoder.in_dtx(), Ok(false));
+    }
+
     #[test]
     fn set_get_vbr_constraint() {
         let mut encoder =
@@ -653,6 +1666,29 @@ mod tests {
         assert_matches!(encoder.force_channels(), Ok(Channels::Auto));
     }
 
+    #[test]
+    fn stereo_forced() {
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        assert_matches!(encoder.stereo_forced(), Ok(false));
+
+        encoder
+            .set_force_channels(Channels::Stereo)
+            .expect("Could not set force channels to stereo.");
+        assert_matches!(encoder.stereo_forced(), Ok(true));
+
+        encoder
+            .set_force_channels(Channels::Mono)
+            .expect("Could not set force channels to mono.");
+        assert_matches!(encoder.stereo_forced(), Ok(false));
+
+        encoder
+            .set_force_channels(Channels::Auto)
+            .expect("Could not set force channels to auto.");
+        assert_matches!(encoder.stereo_forced(), Ok(false));
+    }
+
     #[test]
     fn set_get_prediction_disabled() {
         let mut encoder =
@@ -694,6 +1730,17 @@ mod tests {
         assert_matches!(encoder.signal(), Ok(Signal::Auto));
     }
 
+    #[test]
+    fn debug_queries_configuration() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Voip).unwrap();
+        let debug = format!("{:?}", encoder);
+
+        assert!(debug.contains("channels: Stereo"));
+        assert!(debug.contains("sample_rate: Hz48000"));
+        assert!(debug.contains("application: Voip"));
+    }
+
     #[test]
     fn encoder_construction() {
         assert_matches!(
@@ -733,6 +1780,27 @@ mod tests {
         assert_eq!(&output[..len], &[248, 255, 254]);
     }
 
+    #[test]
+    fn encode_multiframe() {
+        use crate::packet::{nb_frames, Packet};
+        use std::convert::TryFrom;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        // 48000Hz * 1 channel * 60 ms / 1000
+        const MONO_60MS: usize = 48000 * 1 * 60 / 1000;
+        let input = music_like_signal(MONO_60MS);
+
+        let packet = encoder.encode_multiframe(&input, 3, 1500).unwrap();
+        assert_matches!(nb_frames(Packet::try_from(&packet).unwrap()), Ok(3));
+
+        assert_matches!(
+            encoder.encode_multiframe(&input, 2, 1500),
+            Err(Error::SignalsExpectedLen(1920))
+        );
+    }
+
     #[test]
     fn set_max_bandwidth() {
         let mut encoder =
@@ -793,6 +1861,32 @@ mod tests {
         assert_eq!(current_application, application_to_set);
     }
 
+    #[test]
+    fn validate_low_delay_narrowband() {
+        let mut encoder =
+            Encoder::new(SampleRate::Hz8000, Channels::Mono, Application::LowDelay).unwrap();
+        assert_eq!(encoder.validate().unwrap(), [Warning::LowDelayNarrowband]);
+
+        encoder.set_application(Application::Voip).unwrap();
+        assert_eq!(encoder.validate().unwrap(), []);
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::LowDelay).unwrap();
+        assert_eq!(encoder.validate().unwrap(), []);
+    }
+
+    #[test]
+    fn algorithmic_delay() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        let delay = encoder.algorithmic_delay_ms().unwrap();
+        assert!(delay > 2.5 && delay < 30.0);
+
+        let lookahead = encoder.lookahead().unwrap();
+        assert!((delay - lookahead as f32 / 48.0).abs() < f32::EPSILON);
+    }
+
     #[test]
     fn set_get_bitrate() {
         let mut encoder =
@@ -807,6 +1901,450 @@ mod tests {
         assert_matches!(encoder.bitrate(), _bitrate);
     }
 
+    /// Generates a deterministic chord of a few harmonics, this is rich enough
+    /// for the encoder's bitrate to affect the outcome.
+    fn music_like_signal(samples: usize) -> Vec<i16> {
+        (0..samples)
+            .map(|i| {
+                let t = i as f32 / 48000.0;
+                let chord = [220.0, 277.18, 329.63, 440.0, 1760.0]
+                    .iter()
+                    .map(|frequency| (2.0 * std::f32::consts::PI * frequency * t).sin())
+                    .sum::<f32>();
+
+                (chord * 5000.0) as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bitrate_affects_encoded_size() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = music_like_signal(MONO_20MS * 10);
+
+        let encoded_size = |bitrate| {
+            let mut encoder =
+                Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+            encoder
+                .set_bitrate(Bitrate::BitsPerSecond(bitrate))
+                .unwrap();
+
+            let mut output = [0; 1500];
+
+            input
+                .chunks(MONO_20MS)
+                .map(|frame| encoder.encode(frame, &mut output).unwrap())
+                .sum::<usize>()
+        };
+
+        let low_bitrate_size = encoded_size(16000);
+        let high_bitrate_size = encoded_size(128_000);
+
+        assert!(low_bitrate_size * 4 < high_bitrate_size);
+    }
+
+    #[test]
+    fn last_bandwidth_at_low_bitrate() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = music_like_signal(MONO_20MS * 10);
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        encoder.set_bitrate(Bitrate::BitsPerSecond(6000)).unwrap();
+
+        let mut output = [0; 1500];
+        let last_bandwidth = input
+            .chunks(MONO_20MS)
+            .map(|frame| {
+                let len = encoder.encode(frame, &mut output).unwrap();
+                let packet = Packet::try_from(&output[..len]).unwrap();
+
+                encoder.last_bandwidth(packet).unwrap()
+            })
+            .last()
+            .unwrap();
+
+        assert_matches!(last_bandwidth, Bandwidth::Narrowband);
+    }
+
+    #[test]
+    fn new_with_settings() {
+        let settings = EncoderSettings {
+            bitrate: Some(Bitrate::BitsPerSecond(32000)),
+            complexity: Some(3),
+            dtx: Some(true),
+            signal: Some(Signal::Voice),
+            ..EncoderSettings::default()
+        };
+
+        let encoder = Encoder::new_with_settings(
+            SampleRate::Hz48000,
+            Channels::Mono,
+            Application::Voip,
+            &settings,
+        )
+        .unwrap();
+
+        assert_matches!(encoder.bitrate(), Ok(Bitrate::BitsPerSecond(32000)));
+        assert_matches!(encoder.complexity(), Ok(3));
+        assert_matches!(encoder.dtx(), Ok(true));
+        assert_matches!(encoder.signal(), Ok(Signal::Voice));
+        assert_matches!(encoder.vbr(), Ok(true));
+
+        let invalid = EncoderSettings {
+            complexity: Some(11),
+            ..EncoderSettings::default()
+        };
+
+        assert_matches!(
+            Encoder::new_with_settings(
+                SampleRate::Hz48000,
+                Channels::Mono,
+                Application::Voip,
+                &invalid,
+            ),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn set_bitrate_per_channel() {
+        let mut stereo =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        stereo.set_bitrate_per_channel(48000).unwrap();
+        assert_matches!(stereo.bitrate(), Ok(Bitrate::BitsPerSecond(96000)));
+
+        let mut mono =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        mono.set_bitrate_per_channel(48000).unwrap();
+        assert_matches!(mono.bitrate(), Ok(Bitrate::BitsPerSecond(48000)));
+
+        assert_matches!(
+            mono.set_bitrate_per_channel(0),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn build_voip_encoder() {
+        let encoder = EncoderBuilder::new()
+            .application(Application::Voip)
+            .bitrate(Bitrate::BitsPerSecond(24000))
+            .complexity(5)
+            .vbr(true)
+            .vbr_constraint(false)
+            .inband_fec(true)
+            .packet_loss_perc(10)
+            .dtx(true)
+            .force_channels(Channels::Mono)
+            .max_bandwidth(Bandwidth::Wideband)
+            .bandwidth(Bandwidth::Wideband)
+            .signal(Signal::Voice)
+            .lsb_depth(16)
+            .prediction_disabled(true)
+            .build(SampleRate::Hz48000, Channels::Stereo)
+            .unwrap();
+
+        assert_matches!(encoder.application(), Ok(Application::Voip));
+        assert_matches!(encoder.bitrate(), Ok(Bitrate::BitsPerSecond(24000)));
+        assert_matches!(encoder.complexity(), Ok(5));
+        assert_matches!(encoder.vbr(), Ok(true));
+        assert_matches!(encoder.vbr_constraint(), Ok(false));
+        assert_matches!(encoder.inband_fec(), Ok(true));
+        assert_matches!(encoder.packet_loss_perc(), Ok(10));
+        assert_matches!(encoder.dtx(), Ok(true));
+        assert_matches!(encoder.force_channels(), Ok(Channels::Mono));
+        assert_matches!(encoder.max_bandwidth(), Ok(Bandwidth::Wideband));
+        assert_matches!(encoder.signal(), Ok(Signal::Voice));
+        assert_matches!(encoder.lsb_depth(), Ok(16));
+        assert_matches!(encoder.prediction_disabled(), Ok(true));
+
+        let defaults = EncoderBuilder::default()
+            .build(SampleRate::Hz48000, Channels::Mono)
+            .unwrap();
+        assert_matches!(defaults.application(), Ok(Application::Audio));
+        assert_matches!(defaults.complexity(), Ok(9));
+
+        assert_matches!(
+            EncoderBuilder::new()
+                .complexity(11)
+                .build(SampleRate::Hz48000, Channels::Mono),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn reinit_stereo_audio_to_mono_voip() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let memory_usage = encoder.memory_usage();
+        encoder.set_complexity(2).unwrap();
+
+        encoder
+            .reinit(SampleRate::Hz16000, Channels::Mono, Application::Voip)
+            .unwrap();
+
+        assert_eq!(encoder.channels(), Channels::Mono);
+        assert_eq!(encoder.memory_usage(), memory_usage);
+        assert_matches!(encoder.sample_rate(), Ok(SampleRate::Hz16000));
+        assert_matches!(encoder.application(), Ok(Application::Voip));
+        assert_matches!(encoder.complexity(), Ok(9));
+
+        // 320 mono samples at 16kHz are one 20ms frame, as stereo they would
+        // be a 10ms frame.
+        let packet_buffer = encoder.encode_vec(&[0; MONO_20MS / 3], None).unwrap();
+        let packet = Packet::try_from(&packet_buffer).unwrap();
+        assert_matches!(
+            packet::samples_per_frame(packet, SampleRate::Hz16000),
+            Ok(320)
+        );
+        assert_matches!(packet::nb_channels(packet), Ok(Channels::Mono));
+
+        encoder
+            .reinit(SampleRate::Hz48000, Channels::Stereo, Application::Audio)
+            .unwrap();
+        assert_eq!(encoder.channels(), Channels::Stereo);
+
+        let mut mono =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        assert_matches!(
+            mono.reinit(SampleRate::Hz48000, Channels::Stereo, Application::Audio),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+        assert_eq!(mono.channels(), Channels::Mono);
+    }
+
+    #[test]
+    fn reconfigure_mono_to_stereo() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let encoder = encoder
+            .reconfigure(SampleRate::Hz48000, Channels::Stereo, Application::Audio)
+            .unwrap();
+        assert_eq!(encoder.channels(), Channels::Stereo);
+
+        let mut output = [0; 256];
+        assert!(encoder.encode(&[0; STEREO_20MS], &mut output).is_ok());
+
+        let (encoder, error) = encoder
+            .reconfigure(SampleRate::Hz48000, Channels::Auto, Application::Audio)
+            .unwrap_err();
+        assert_matches!(error, Error::Opus(ErrorCode::BadArgument));
+        assert_eq!(encoder.channels(), Channels::Stereo);
+    }
+
+    #[test]
+    fn set_cvbr() {
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        encoder.set_vbr(false).unwrap();
+        encoder.set_vbr_constraint(false).unwrap();
+
+        encoder.set_cvbr(Bitrate::BitsPerSecond(96000)).unwrap();
+
+        assert_matches!(encoder.vbr(), Ok(true));
+        assert_matches!(encoder.vbr_constraint(), Ok(true));
+        assert_matches!(encoder.bitrate(), Ok(Bitrate::BitsPerSecond(96000)));
+    }
+
+    #[test]
+    fn encode_with_level() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let mut output = [0; 1500];
+
+        // A square wave at half of full scale is 20 * log10(0.5) dBFS.
+        let half_scale = (0..MONO_20MS)
+            .map(|i| if i / 24 % 2 == 0 { 16384 } else { -16384 })
+            .collect::<Vec<i16>>();
+        let (len, level) = encoder.encode_with_level(&half_scale, &mut output).unwrap();
+
+        assert!(len > 0);
+        assert!((level - -6.0206).abs() < 0.001);
+
+        let (_, level) = encoder
+            .encode_with_level(&[0; MONO_20MS], &mut output)
+            .unwrap();
+        assert!(level.is_infinite() && level.is_sign_negative());
+    }
+
+    #[test]
+    fn encode_checked_clip() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let mut output = [0; 1500];
+
+        let quiet = pcm::sine(440.0, SampleRate::Hz48000, Channels::Mono, MONO_20MS);
+        let (len, clipped) = encoder.encode_checked_clip(&quiet, &mut output).unwrap();
+        assert!(len > 0);
+        assert!(!clipped);
+
+        let mut loud = quiet;
+        loud[MONO_20MS / 2] = i16::MIN;
+        let (_, clipped) = encoder.encode_checked_clip(&loud, &mut output).unwrap();
+        assert!(clipped);
+    }
+
+    #[test]
+    fn encode_silence() {
+        use crate::{coder::Decoder, packet::Packet};
+        use std::convert::{TryFrom, TryInto};
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Voip).unwrap();
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut output = vec![0_i16; 1920];
+
+        for _ in 0..5 {
+            let packet = encoder.encode_silence(960, 1500).unwrap();
+            assert!(packet.len() <= 10);
+
+            assert_matches!(
+                decoder.decode(
+                    Some(Packet::try_from(&packet).unwrap()),
+                    (&mut output).try_into().unwrap(),
+                    false
+                ),
+                Ok(960)
+            );
+        }
+
+        assert_matches!(
+            encoder.encode_silence(100, 1500),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn encode_within() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = music_like_signal(MONO_20MS * 5);
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        encoder
+            .set_bitrate(Bitrate::BitsPerSecond(128_000))
+            .unwrap();
+
+        for frame in input.chunks(MONO_20MS) {
+            let packet = encoder.encode_within(frame, 40).unwrap();
+
+            assert!(!packet.is_empty());
+            assert!(packet.len() <= 40);
+        }
+
+        assert_matches!(encoder.bitrate(), Ok(Bitrate::BitsPerSecond(128_000)));
+
+        assert_matches!(
+            encoder.encode_within(&input[..MONO_20MS], 0),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+        assert_matches!(encoder.bitrate(), Ok(Bitrate::BitsPerSecond(128_000)));
+    }
+
+    #[test]
+    fn encode_within_retries_on_clone() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = music_like_signal(MONO_20MS * 2);
+        let (first, second) = input.split_at(MONO_20MS);
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        encoder
+            .set_bitrate(Bitrate::BitsPerSecond(128_000))
+            .unwrap();
+        // Constant bitrate makes every packet's size follow the bitrate.
+        encoder.set_vbr(false).unwrap();
+
+        // 40 bytes per 20ms frame average 16000 bits per second.
+        let mut expected = encoder.clone();
+        expected
+            .set_bitrate(Bitrate::BitsPerSecond(16_000))
+            .unwrap();
+        let expected_packet = expected.encode_vec(first, None).unwrap();
+        assert_eq!(expected_packet.len(), 40);
+
+        assert_eq!(encoder.encode_within(first, 40).unwrap(), expected_packet);
+
+        // The encoder continues from the retried frame's state.
+        expected
+            .set_bitrate(Bitrate::BitsPerSecond(128_000))
+            .unwrap();
+        assert_eq!(
+            encoder.encode_vec(second, None).unwrap(),
+            expected.encode_vec(second, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn complexity_affects_output() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = music_like_signal(MONO_20MS * 5);
+
+        let encode = |complexity| {
+            let mut encoder =
+                Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+            encoder.set_complexity(complexity).unwrap();
+
+            let mut output = [0; 1500];
+            let packets = input
+                .chunks(MONO_20MS)
+                .map(|frame| {
+                    let len = encoder.encode(frame, &mut output).unwrap();
+                    output[..len].to_vec()
+                })
+                .collect::<Vec<_>>();
+
+            (packets, encoder.final_range().unwrap())
+        };
+
+        assert_ne!(encode(0), encode(10));
+    }
+
+    #[test]
+    fn ramp_bitrate() {
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        encoder.set_bitrate(Bitrate::BitsPerSecond(32000)).unwrap();
+
+        let target = Bitrate::BitsPerSecond(128_000);
+
+        for expected in (48000..=128_000).step_by(16000) {
+            assert_matches!(
+                encoder.ramp_bitrate(target, 16000),
+                Ok(Bitrate::BitsPerSecond(bits)) if bits == expected
+            );
+            assert_matches!(encoder.bitrate(), Ok(Bitrate::BitsPerSecond(bits)) if bits == expected);
+        }
+
+        assert_matches!(
+            encoder.ramp_bitrate(target, 16000),
+            Ok(Bitrate::BitsPerSecond(128_000))
+        );
+
+        assert_matches!(
+            encoder.ramp_bitrate(Bitrate::BitsPerSecond(100_000), 16000),
+            Ok(Bitrate::BitsPerSecond(112_000))
+        );
+    }
+
     #[test]
     fn set_get_dtx() {
         let mut encoder =
@@ -821,6 +2359,166 @@ mod tests {
         assert_matches!(encoder.dtx(), Ok(false));
     }
 
+    #[test]
+    fn clone_produces_identical_packets() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Voip).unwrap();
+        encoder.set_bitrate(Bitrate::BitsPerSecond(24000)).unwrap();
+        encoder.set_complexity(3).unwrap();
+
+        let signal = pcm::sine(440.0, SampleRate::Hz48000, Channels::Stereo, 960 * 3);
+        encoder.encode_vec(&signal[..STEREO_20MS], None).unwrap();
+
+        let forked = encoder.clone();
+        assert_eq!(forked.channels(), Channels::Stereo);
+        assert_matches!(forked.application(), Ok(Application::Voip));
+        assert_matches!(forked.complexity(), Ok(3));
+
+        for frame in signal[STEREO_20MS..].chunks(STEREO_20MS) {
+            assert_eq!(
+                encoder.encode_vec(frame, None).unwrap(),
+                forked.encode_vec(frame, None).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn reset_keeps_application() {
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        encoder.set_application(Application::LowDelay).unwrap();
+        encoder.reset_state().unwrap();
+        assert_matches!(encoder.application(), Ok(Application::LowDelay));
+
+        encoder.set_application(Application::Voip).unwrap();
+        encoder.reset_state().unwrap();
+        assert_matches!(encoder.application(), Ok(Application::Voip));
+    }
+
+    #[test]
+    fn encode_vec_truncates_to_encoded_len() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let mut output = [0; 1500];
+        let len = encoder.encode(&[0; STEREO_20MS], &mut output).unwrap();
+
+        encoder.reset_state().unwrap();
+        let packet = encoder.encode_vec(&[0; STEREO_20MS], None).unwrap();
+        assert_eq!(packet.len(), len);
+        assert_eq!(packet, &output[..len]);
+
+        encoder.reset_state().unwrap();
+        let float_len = encoder
+            .encode_float(&[0.0; STEREO_20MS], &mut output)
+            .unwrap();
+
+        encoder.reset_state().unwrap();
+        let float_packet = encoder.encode_float_vec(&[0.0; STEREO_20MS], None).unwrap();
+        assert_eq!(float_packet.len(), float_len);
+        assert_eq!(float_packet, &output[..float_len]);
+
+        assert_matches!(
+            encoder.encode_vec(&[0; STEREO_20MS], Some(0)),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn encode_vec_defaults_to_worst_case_len() {
+        // 48000Hz * 2 channels * 60 ms / 1000
+        const STEREO_60MS: usize = 48000 * 2 * 60 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        encoder.set_bitrate(Bitrate::Max).unwrap();
+
+        let input = (0..STEREO_60MS)
+            .map(|i| (((i * 7919) % 60000) as i32 - 30000) as i16)
+            .collect::<Vec<_>>();
+        let packet = encoder.encode_vec(&input, None).unwrap();
+
+        assert!(packet.len() > max_packet_bytes(1));
+        assert!(packet.len() <= max_packet_bytes(3));
+    }
+
+    #[test]
+    fn dtx_shortens_silent_packets() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip).unwrap();
+        encoder.enable_dtx().unwrap();
+        let mut output = [0; 1500];
+
+        let packet_lens = (0..50)
+            .map(|_| encoder.encode(&[0; MONO_20MS], &mut output).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(packet_lens.iter().any(|&len| len <= 2));
+        assert_matches!(encoder.in_dtx(), Ok(true));
+    }
+
+    #[test]
+    fn stereo_uses_more_memory_than_mono() {
+        let mono = Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let stereo =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        assert!(mono.memory_usage() > 0);
+        assert!(stereo.memory_usage() > mono.memory_usage());
+    }
+
+    #[test]
+    fn created_encoder_is_allocated() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        assert!(!encoder.pointer.is_null());
+    }
+
+    #[test]
+    fn encoder_size() {
+        let mono = Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        assert!(size(Channels::Mono) > 0);
+        assert!(size(Channels::Stereo) > size(Channels::Mono));
+        assert_eq!(mono.size(), size(Channels::Mono));
+    }
+
+    #[test]
+    fn set_get_phase_inversion_disabled() {
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        assert_matches!(encoder.phase_inversion_disabled(), Ok(false));
+
+        encoder
+            .set_phase_inversion_disabled(true)
+            .expect("Could not disable phase inversion.");
+        assert_matches!(encoder.phase_inversion_disabled(), Ok(true));
+        assert_matches!(
+            encoder.ctl_get(ffi::OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST),
+            Ok(1)
+        );
+
+        encoder
+            .set_phase_inversion_disabled(false)
+            .expect("Could not enable phase inversion.");
+        assert_matches!(encoder.phase_inversion_disabled(), Ok(false));
+        assert_matches!(
+            encoder.ctl_get(ffi::OPUS_GET_PHASE_INVERSION_DISABLED_REQUEST),
+            Ok(0)
+        );
+    }
+
     #[test]
     fn set_get_lsb_depth() {
         let mut encoder =
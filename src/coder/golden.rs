@@ -0,0 +1,143 @@
+//! Golden test harness guarding against silent changes of Opus' output.
+//!
+//! A fixed signal is encoded and decoded, the packets and decoded signal are
+//! compared byte for byte against a golden file checked in under
+//! `src/coder/golden/`. Opus' output differs between versions, fixed- and
+//! floating-point builds, and architectures, hence each golden file is tagged
+//! with the Opus version string, the target architecture, and the sizes of
+//! Opus' encoder and decoder states.
+//!
+//! Builds without a release tag, such as the Opus bundled with
+//! `audiopus_sys`, report their version as `libopus unknown`. The state
+//! sizes change with Opus' version and build options, telling those builds
+//! apart.
+//!
+//! If no golden file exists for the running configuration, the comparison
+//! is skipped with a note, unless the environment variable
+//! `AUDIOPUS_REQUIRE_GOLDEN` is set, failing the test instead.
+//! Setting the environment variable `AUDIOPUS_REGENERATE_GOLDEN` writes the
+//! current output as golden file instead of comparing, after a deliberate
+//! Opus upgrade its changes should be reviewed and committed.
+
+use super::{encoder_size, size as decoder_size, Decoder, Encoder};
+use crate::{packet::Packet, version, Application, Channels, SampleRate};
+use std::{
+    convert::{TryFrom, TryInto},
+    env, fs,
+    path::PathBuf,
+};
+
+/// Setting this environment variable regenerates the golden file.
+const REGENERATE_VAR: &str = "AUDIOPUS_REGENERATE_GOLDEN";
+
+/// Setting this environment variable fails the test if no golden file exists.
+const REQUIRE_VAR: &str = "AUDIOPUS_REQUIRE_GOLDEN";
+
+// 48000Hz * 2 channels * 20 ms / 1000
+const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+const FRAMES: usize = 10;
+
+/// Returns the golden file's path for the running Opus version, architecture
+/// and Opus' stereo encoder and decoder state sizes.
+fn golden_path() -> PathBuf {
+    let version = version()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/coder/golden")
+        .join(format!(
+            "{}-{}-e{}-d{}.bin",
+            version,
+            env::consts::ARCH,
+            encoder_size(Channels::Stereo),
+            decoder_size(Channels::Stereo)
+        ))
+}
+
+/// A deterministic stereo signal computed with integer arithmetic only, to
+/// rule out floating-point differences in the input.
+fn golden_signal() -> Vec<i16> {
+    (0..STEREO_20MS * FRAMES)
+        .map(|i| (((i * 7919 + i / 97 * 31) % 40000) as i32 - 20000) as i16)
+        .collect()
+}
+
+/// Encodes and decodes the golden signal, serialising each packet prefixed
+/// by its big-endian `u16` length, followed by the decoded signal as
+/// little-endian `i16` samples.
+fn golden_output() -> Vec<u8> {
+    let encoder =
+        Encoder::fresh_for_testing(SampleRate::Hz48000, Channels::Stereo, Application::Audio)
+            .unwrap();
+    let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+
+    let mut output = Vec::new();
+    let mut pcm = Vec::new();
+
+    for frame in golden_signal().chunks(STEREO_20MS) {
+        let packet = encoder.encode_vec(frame, None).unwrap();
+
+        output.extend_from_slice(&u16::try_from(packet.len()).unwrap().to_be_bytes());
+        output.extend_from_slice(&packet);
+
+        let mut frame_pcm = vec![0_i16; STEREO_20MS];
+        decoder
+            .decode(
+                Some(Packet::try_from(&packet).unwrap()),
+                (&mut frame_pcm).try_into().unwrap(),
+                false,
+            )
+            .unwrap();
+        pcm.extend(frame_pcm);
+    }
+
+    for sample in pcm {
+        output.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    output
+}
+
+#[test]
+fn matches_golden_file() {
+    let path = golden_path();
+    let output = golden_output();
+
+    if env::var_os(REGENERATE_VAR).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &output).unwrap();
+
+        return;
+    }
+
+    if let Ok(golden) = fs::read(&path) {
+        assert!(
+            golden == output,
+            "Opus' output differs from {}. Review the change and regenerate \
+             the file by setting `{}`.",
+            path.display(),
+            REGENERATE_VAR
+        );
+    } else {
+        assert!(
+            env::var_os(REQUIRE_VAR).is_none(),
+            "No golden file at {} for this Opus build. Generate it by setting \
+             `{}` and commit it.",
+            path.display(),
+            REGENERATE_VAR
+        );
+        eprintln!(
+            "Skipping the golden comparison, there is no golden file at {} for \
+             this Opus build.",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,863 @@
+use super::GenericCtl;
+use crate::{
+    error::{create_error, try_map_opus_error},
+    ffi,
+    packet::Packet,
+    Application, Error, MutSignals, Result, SampleRate, TryFrom,
+};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+/// Marks an output channel as silent in a channel mapping.
+pub const SILENT_CHANNEL: u8 = 255;
+
+/// Builds and validates the channel mapping of a multistream coder.
+///
+/// The mapping assigns each output channel, in order, one of the channels
+/// decoded from the streams. Coupled streams decode to two channels and come
+/// first, the remaining streams decode to one channel each, giving
+/// `streams + coupled_streams` decoded channels in total.
+///
+/// ```
+/// use audiopus::coder::multistream::MappingBuilder;
+///
+/// // Stereo from one coupled stream, followed by a mono stream.
+/// let mapping = MappingBuilder::new(3, 2, 1)
+///     .channel(0)
+///     .channel(1)
+///     .channel(2)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(mapping, vec![0, 1, 2]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MappingBuilder {
+    channels: u8,
+    streams: u8,
+    coupled_streams: u8,
+    mapping: Vec<u8>,
+}
+
+impl MappingBuilder {
+    /// Starts a mapping for `channels` output channels, encoded in `streams`
+    /// streams of which `coupled_streams` are stereo.
+    #[must_use]
+    pub fn new(channels: u8, streams: u8, coupled_streams: u8) -> Self {
+        Self {
+            channels,
+            streams,
+            coupled_streams,
+            mapping: Vec::with_capacity(usize::from(channels)),
+        }
+    }
+
+    /// Maps the next output channel to the `decoded_channel`.
+    #[must_use]
+    pub fn channel(mut self, decoded_channel: u8) -> Self {
+        self.mapping.push(decoded_channel);
+
+        self
+    }
+
+    /// Maps the next output channel to silence.
+    #[must_use]
+    pub fn silent(self) -> Self {
+        self.channel(SILENT_CHANNEL)
+    }
+
+    /// Validates and returns the mapping.
+    ///
+    /// **Errors**:
+    /// Returns [Error::MappingExpectedLen] with the channel count when not
+    /// every output channel has been mapped exactly once.
+    ///
+    /// Returns [Error::InvalidMapping] with the first entry referencing a
+    /// decoded channel that does not exist.
+    ///
+    /// [Error::MappingExpectedLen]: crate::error::Error::MappingExpectedLen
+    /// [Error::InvalidMapping]: crate::error::Error::InvalidMapping
+    pub fn build(self) -> Result<Vec<u8>> {
+        if self.mapping.len() != usize::from(self.channels) {
+            return Err(Error::MappingExpectedLen(usize::from(self.channels)));
+        }
+
+        let decoded_channels = u16::from(self.streams) + u16::from(self.coupled_streams);
+
+        if let Some(&invalid) = self
+            .mapping
+            .iter()
+            .find(|&&entry| entry != SILENT_CHANNEL && u16::from(entry) >= decoded_channels)
+        {
+            return Err(Error::InvalidMapping(invalid));
+        }
+
+        Ok(self.mapping)
+    }
+}
+
+/// `MsEncoder` encodes more than two channels, e.g. surround sound, into
+/// multistream packets.
+///
+/// Each stream is an Opus stream of its own, coupled streams encode two
+/// channels, the remaining streams encode one channel each.
+pub struct MsEncoder {
+    pointer: *mut ffi::OpusMSEncoder,
+    channels: u8,
+    streams: u8,
+    coupled_streams: u8,
+}
+
+/// The Opus multistream encoder can be sent between threads unless the Opus
+/// library has been compiled with `NONTHREADSAFE_PSEUDOSTACK` to disallow
+/// encoding in parallel.
+unsafe impl Send for MsEncoder {}
+
+impl Debug for MsEncoder {
+    /// Besides the fields, the sample rate is queried from Opus, a failed
+    /// query is displayed as `<unavailable>`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut debug = f.debug_struct("MsEncoder");
+        debug
+            .field("pointer", &self.pointer)
+            .field("channels", &self.channels)
+            .field("streams", &self.streams)
+            .field("coupled_streams", &self.coupled_streams);
+
+        match self.sample_rate() {
+            Ok(sample_rate) => debug.field("sample_rate", &sample_rate),
+            Err(_) => debug.field("sample_rate", &format_args!("<unavailable>")),
+        };
+
+        debug.finish()
+    }
+}
+
+impl GenericCtl for MsEncoder {
+    fn ctl_get(&self, request: i32) -> Result<i32> {
+        self.encoder_ctl_request(request)
+    }
+
+    fn ctl_set(&mut self, request: i32, value: i32) -> Result<()> {
+        self.set_encoder_ctl_request(request, value)
+    }
+
+    /// Gets the final state of the codec's entropy coder, combined over all
+    /// streams.
+    ///
+    /// This is used for testing purposes. The encoder state should
+    /// be identical after coding a payload, assuming no data corruption or
+    /// software bugs.
+    fn final_range(&self) -> Result<u32> {
+        self.encoder_ctl_request(ffi::OPUS_GET_FINAL_RANGE_REQUEST)
+            .map(|v| v as u32)
+    }
+
+    /// Gets the sampling rate the encoder was initialized with.
+    ///
+    /// This simply returns the Fs value passed to [`MsEncoder::new`].
+    ///
+    /// [`MsEncoder::new`]: struct.MsEncoder.html#method.new
+    fn sample_rate(&self) -> Result<SampleRate> {
+        self.encoder_ctl_request(ffi::OPUS_GET_SAMPLE_RATE_REQUEST)
+            .and_then(SampleRate::try_from)
+    }
+
+    /// Resets the state of every stream to be equivalent to a freshly
+    /// initialized state.
+    fn reset_state(&mut self) -> Result<()> {
+        self.encoder_ctl_request(ffi::OPUS_RESET_STATE).map(|_| ())
+    }
+}
+
+impl MsEncoder {
+    /// Creates a new Opus multistream encoder encoding `channels` channels
+    /// in `streams` streams of which `coupled_streams` are stereo.
+    ///
+    /// The `mapping` assigns each input channel, in order, the channel of
+    /// the streams it is encoded in, see [`MappingBuilder`].
+    ///
+    /// **Errors**:
+    /// Returns [Error::MappingExpectedLen] with the channel count when the
+    /// `mapping` does not map every input channel exactly once.
+    ///
+    /// Returns [Error::Opus] when Opus rejected the configuration, e.g.
+    /// `coupled_streams` exceeding `streams`.
+    ///
+    /// [`MappingBuilder`]: struct.MappingBuilder.html
+    /// [Error::MappingExpectedLen]: crate::error::Error::MappingExpectedLen
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn new(
+        sample_rate: SampleRate,
+        channels: u8,
+        streams: u8,
+        coupled_streams: u8,
+        mapping: &[u8],
+        mode: Application,
+    ) -> Result<MsEncoder> {
+        if mapping.len() != usize::from(channels) {
+            return Err(Error::MappingExpectedLen(usize::from(channels)));
+        }
+
+        let mut opus_code = 0;
+
+        let pointer = unsafe {
+            ffi::opus_multistream_encoder_create(
+                sample_rate as i32,
+                i32::from(channels),
+                i32::from(streams),
+                i32::from(coupled_streams),
+                mapping.as_ptr(),
+                mode as i32,
+                &mut opus_code,
+            )
+        };
+
+        if opus_code == ffi::OPUS_OK && !pointer.is_null() {
+            return Ok(MsEncoder {
+                pointer,
+                channels,
+                streams,
+                coupled_streams,
+            });
+        }
+
+        if !pointer.is_null() {
+            unsafe { ffi::opus_multistream_encoder_destroy(pointer) }
+        }
+
+        Err(create_error(opus_code))
+    }
+
+    /// Issues a CTL get-`request` to Opus.
+    /// If Opus returns a negative value it indicates an error.
+    ///
+    /// **Info**:
+    /// Settings are applied to and read from the streams' encoders as Opus
+    /// defines for multistream encoders.
+    pub fn encoder_ctl_request(&self, request: i32) -> Result<i32> {
+        let mut value = 0;
+
+        let ffi_result =
+            unsafe { ffi::opus_multistream_encoder_ctl(self.pointer, request, &mut value) };
+        try_map_opus_error(ffi_result)?;
+
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "MsEncoder got {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
+        Ok(value)
+    }
+
+    /// Issues a CTL set-`request` to Opus and sets the `MsEncoder`'s setting
+    /// to `value` based on sent `request`.
+    /// If Opus returns a negative value it indicates an error.
+    pub fn set_encoder_ctl_request(&mut self, request: i32, value: i32) -> Result<()> {
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "MsEncoder set {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
+        try_map_opus_error(unsafe {
+            ffi::opus_multistream_encoder_ctl(self.pointer, request, value)
+        })?;
+
+        Ok(())
+    }
+
+    /// Gets the amount of channels the encoder was created with.
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    /// Gets the amount of streams the encoder was created with.
+    pub fn streams(&self) -> u8 {
+        self.streams
+    }
+
+    /// Gets the amount of coupled streams the encoder was created with.
+    pub fn coupled_streams(&self) -> u8 {
+        self.coupled_streams
+    }
+
+    /// Gets the heap memory in bytes Opus allocated for this encoder, the
+    /// sum over the encoders of all streams.
+    pub fn memory_usage(&self) -> usize {
+        unsafe {
+            ffi::opus_multistream_encoder_get_size(
+                i32::from(self.streams),
+                i32::from(self.coupled_streams),
+            ) as usize
+        }
+    }
+
+    /// Encodes a multistream Opus frame.
+    ///
+    /// The `input` signal (interleaved across all channels) will be encoded
+    /// into the `output` payload and on success returns the length of the
+    /// encoded packet.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode(&self, input: &[i16], output: &mut [u8]) -> Result<usize> {
+        try_map_opus_error(unsafe {
+            ffi::opus_multistream_encode(
+                self.pointer,
+                input.as_ptr(),
+                input.len() as i32 / i32::from(self.channels),
+                output.as_mut_ptr(),
+                output.len() as i32,
+            )
+        })
+        .map(|n| n as usize)
+    }
+
+    /// Encodes a multistream Opus frame from floating point input.
+    ///
+    /// The `input` signal (interleaved across all channels) will be encoded
+    /// into the `output` payload and on success returns the length of the
+    /// encoded packet.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn encode_float(&self, input: &[f32], output: &mut [u8]) -> Result<usize> {
+        try_map_opus_error(unsafe {
+            ffi::opus_multistream_encode_float(
+                self.pointer,
+                input.as_ptr(),
+                input.len() as i32 / i32::from(self.channels),
+                output.as_mut_ptr(),
+                output.len() as i32,
+            )
+        })
+        .map(|n| n as usize)
+    }
+}
+
+impl Drop for MsEncoder {
+    /// We have to ensure that the resource our wrapping Opus-struct is pointing
+    /// to is deallocated properly.
+    fn drop(&mut self) {
+        unsafe { ffi::opus_multistream_encoder_destroy(self.pointer) }
+    }
+}
+
+/// `MsDecoder` decodes multistream packets, e.g. produced by [`MsEncoder`],
+/// into more than two channels.
+///
+/// [`MsEncoder`]: struct.MsEncoder.html
+pub struct MsDecoder {
+    pointer: *mut ffi::OpusMSDecoder,
+    channels: u8,
+    streams: u8,
+    coupled_streams: u8,
+}
+
+/// The Opus multistream decoder can be sent between threads unless the Opus
+/// library has been compiled with `NONTHREADSAFE_PSEUDOSTACK` to disallow
+/// decoding in parallel.
+unsafe impl Send for MsDecoder {}
+
+impl Debug for MsDecoder {
+    /// Besides the fields, the sample rate is queried from Opus, a failed
+    /// query is displayed as `<unavailable>`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut debug = f.debug_struct("MsDecoder");
+        debug
+            .field("pointer", &self.pointer)
+            .field("channels", &self.channels)
+            .field("streams", &self.streams)
+            .field("coupled_streams", &self.coupled_streams);
+
+        match self.sample_rate() {
+            Ok(sample_rate) => debug.field("sample_rate", &sample_rate),
+            Err(_) => debug.field("sample_rate", &format_args!("<unavailable>")),
+        };
+
+        debug.finish()
+    }
+}
+
+impl GenericCtl for MsDecoder {
+    fn ctl_get(&self, request: i32) -> Result<i32> {
+        self.decoder_ctl_request(request)
+    }
+
+    fn ctl_set(&mut self, request: i32, value: i32) -> Result<()> {
+        self.set_decoder_ctl_request(request, value)
+    }
+
+    fn final_range(&self) -> Result<u32> {
+        self.decoder_ctl_request(ffi::OPUS_GET_FINAL_RANGE_REQUEST)
+            .map(|v| v as u32)
+    }
+
+    fn sample_rate(&self) -> Result<SampleRate> {
+        self.decoder_ctl_request(ffi::OPUS_GET_SAMPLE_RATE_REQUEST)
+            .and_then(SampleRate::try_from)
+    }
+
+    fn reset_state(&mut self) -> Result<()> {
+        self.decoder_ctl_request(ffi::OPUS_RESET_STATE).map(|_| ())
+    }
+}
+
+impl MsDecoder {
+    /// Creates a new Opus multistream decoder outputting `channels` channels
+    /// decoded from `streams` streams of which `coupled_streams` are stereo.
+    ///
+    /// The `mapping` assigns each output channel, in order, one of the
+    /// decoded channels, see [`MappingBuilder`].
+    ///
+    /// **Errors**:
+    /// Returns [Error::MappingExpectedLen] with the channel count when the
+    /// `mapping` does not map every output channel exactly once.
+    ///
+    /// Returns [Error::Opus] when Opus rejected the configuration, e.g.
+    /// `coupled_streams` exceeding `streams`.
+    ///
+    /// [`MappingBuilder`]: struct.MappingBuilder.html
+    /// [Error::MappingExpectedLen]: crate::error::Error::MappingExpectedLen
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn new(
+        sample_rate: SampleRate,
+        channels: u8,
+        streams: u8,
+        coupled_streams: u8,
+        mapping: &[u8],
+    ) -> Result<MsDecoder> {
+        if mapping.len() != usize::from(channels) {
+            return Err(Error::MappingExpectedLen(usize::from(channels)));
+        }
+
+        let mut opus_code = 0;
+
+        let pointer = unsafe {
+            ffi::opus_multistream_decoder_create(
+                sample_rate as i32,
+                i32::from(channels),
+                i32::from(streams),
+                i32::from(coupled_streams),
+                mapping.as_ptr(),
+                &mut opus_code,
+            )
+        };
+
+        if opus_code == ffi::OPUS_OK && !pointer.is_null() {
+            return Ok(MsDecoder {
+                pointer,
+                channels,
+                streams,
+                coupled_streams,
+            });
+        }
+
+        if !pointer.is_null() {
+            unsafe { ffi::opus_multistream_decoder_destroy(pointer) }
+        }
+
+        Err(create_error(opus_code))
+    }
+
+    /// Decodes a multistream Opus packet as `input` and writes decoded data
+    /// into `output`, interleaved across all channels.
+    /// Passing `None` as `input` indicates a packet loss.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// Returns [Error::MappingExpectedLen] when the length of `output` is not
+    /// a multiple of the decoder's channels.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [Error::MappingExpectedLen]: crate::error::Error::MappingExpectedLen
+    pub fn decode(
+        &mut self,
+        input: Option<Packet<'_>>,
+        mut output: MutSignals<'_, i16>,
+        fec: bool,
+    ) -> Result<usize> {
+        self.check_output_alignment(output.i32_len())?;
+
+        let (input_pointer, input_len) = if let Some(value) = input {
+            (value.as_ptr(), value.i32_len())
+        } else {
+            (std::ptr::null(), 0)
+        };
+
+        try_map_opus_error(unsafe {
+            ffi::opus_multistream_decode(
+                self.pointer,
+                input_pointer,
+                input_len,
+                output.as_mut_ptr(),
+                output.i32_len() / i32::from(self.channels),
+                fec as i32,
+            )
+        })
+        .map(|n| n as usize)
+    }
+
+    /// Decodes a multistream Opus packet like [`decode`] into floating point
+    /// `output`.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem.
+    ///
+    /// Returns [Error::MappingExpectedLen] when the length of `output` is not
+    /// a multiple of the decoder's channels.
+    ///
+    /// [`decode`]: struct.MsDecoder.html#method.decode
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [Error::MappingExpectedLen]: crate::error::Error::MappingExpectedLen
+    pub fn decode_float(
+        &mut self,
+        input: Option<Packet<'_>>,
+        mut output: MutSignals<'_, f32>,
+        fec: bool,
+    ) -> Result<usize> {
+        self.check_output_alignment(output.i32_len())?;
+
+        let (input_pointer, input_len) = if let Some(value) = input {
+            (value.as_ptr(), value.i32_len())
+        } else {
+            (std::ptr::null(), 0)
+        };
+
+        try_map_opus_error(unsafe {
+            ffi::opus_multistream_decode_float(
+                self.pointer,
+                input_pointer,
+                input_len,
+                output.as_mut_ptr(),
+                output.i32_len() / i32::from(self.channels),
+                fec as i32,
+            )
+        })
+        .map(|n| n as usize)
+    }
+
+    /// Conceals a lost packet by letting Opus generate `frame_samples`
+    /// samples per channel of replacement signal, interleaved across all
+    /// channels.
+    ///
+    /// This is the same as passing `None` to [`decode`].
+    ///
+    /// **Info**:
+    /// Opus only conceals the loss of whole packets. A multistream packet
+    /// carries all its streams, losing only some of them cannot be signalled
+    /// to Opus, the entire packet must be treated as lost.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g.
+    /// `frame_samples` not being a multiple of 2.5ms.
+    ///
+    /// [`decode`]: struct.MsDecoder.html#method.decode
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn conceal(&mut self, frame_samples: usize) -> Result<Vec<i16>> {
+        let channels = usize::from(self.channels);
+        let mut output = vec![0; frame_samples * channels];
+
+        let samples = self.decode(None, MutSignals::try_from(&mut output)?, false)?;
+        output.truncate(samples * channels);
+
+        Ok(output)
+    }
+
+    fn check_output_alignment(&self, output_len: i32) -> Result<()> {
+        if self.channels == 0 || output_len % i32::from(self.channels) != 0 {
+            return Err(Error::MappingExpectedLen(usize::from(self.channels)));
+        }
+
+        Ok(())
+    }
+
+    /// Issues a CTL `request` to Opus without argument used to
+    /// request a value.
+    /// If Opus returns a value smaller than 0, it indicates an error.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    fn decoder_ctl_request(&self, request: i32) -> Result<i32> {
+        let mut value = 0;
+
+        let ffi_result =
+            unsafe { ffi::opus_multistream_decoder_ctl(self.pointer, request, &mut value) };
+
+        try_map_opus_error(ffi_result)?;
+
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "MsDecoder got {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
+        Ok(value)
+    }
+
+    /// Issues a CTL `request` to Opus accepting an additional argument used
+    /// to set the decoder's setting to `value`.
+    /// If Opus returns a value smaller than 0, it indicates an error.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    fn set_decoder_ctl_request(&mut self, request: i32, value: i32) -> Result<()> {
+        #[cfg(feature = "ctl-trace")]
+        log::trace!(
+            "MsDecoder set {}: {}",
+            super::ctl_trace::request_name(request),
+            value
+        );
+
+        try_map_opus_error(unsafe {
+            ffi::opus_multistream_decoder_ctl(self.pointer, request, value)
+        })?;
+
+        Ok(())
+    }
+
+    /// Gets the amount of channels the decoder was created with.
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    /// Gets the amount of streams the decoder was created with.
+    pub fn streams(&self) -> u8 {
+        self.streams
+    }
+
+    /// Gets the amount of coupled streams the decoder was created with.
+    pub fn coupled_streams(&self) -> u8 {
+        self.coupled_streams
+    }
+}
+
+impl Drop for MsDecoder {
+    /// We have to ensure that the resource our wrapping Opus-struct is pointing
+    /// to is deallocated properly.
+    fn drop(&mut self) {
+        unsafe { ffi::opus_multistream_decoder_destroy(self.pointer) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MappingBuilder, MsDecoder, MsEncoder};
+    use crate::{
+        coder::GenericCtl, packet::Packet, Application, Error, ErrorCode, MutSignals, SampleRate,
+    };
+    use matches::assert_matches;
+    use std::convert::TryFrom;
+
+    // Quadraphonic from two coupled streams.
+    fn quad_encoder() -> MsEncoder {
+        let mapping = MappingBuilder::new(4, 2, 2)
+            .channel(0)
+            .channel(1)
+            .channel(2)
+            .channel(3)
+            .build()
+            .unwrap();
+
+        MsEncoder::new(SampleRate::Hz48000, 4, 2, 2, &mapping, Application::Audio).unwrap()
+    }
+
+    #[test]
+    fn ms_encoder_construction() {
+        assert_matches!(
+            MsEncoder::new(SampleRate::Hz48000, 4, 2, 2, &[0, 1, 2], Application::Audio),
+            Err(Error::MappingExpectedLen(4))
+        );
+
+        assert_matches!(
+            MsEncoder::new(SampleRate::Hz48000, 2, 1, 2, &[0, 1], Application::Audio),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+
+        let encoder = quad_encoder();
+        assert_eq!(encoder.channels(), 4);
+        assert_eq!(encoder.streams(), 2);
+        assert_eq!(encoder.coupled_streams(), 2);
+        assert_matches!(encoder.sample_rate(), Ok(SampleRate::Hz48000));
+        assert!(encoder.memory_usage() > 0);
+    }
+
+    #[test]
+    fn ms_encode_quad() {
+        // 48000Hz * 4 channels * 20 ms / 1000
+        const QUAD_20MS: usize = 48000 * 4 * 20 / 1000;
+
+        let mut encoder = quad_encoder();
+        let input = (0..QUAD_20MS)
+            .map(|i| ((i * 131) % 8192) as i16 - 4096)
+            .collect::<Vec<_>>();
+        let mut output = [0; 4000];
+
+        let len = encoder.encode(&input, &mut output).unwrap();
+        assert!(len > 0);
+        let final_range = encoder.final_range().unwrap();
+
+        encoder.reset_state().unwrap();
+        let float_input = input
+            .iter()
+            .map(|&sample| f32::from(sample) / 32768.0)
+            .collect::<Vec<_>>();
+        let mut float_output = [0; 4000];
+        let float_len = encoder
+            .encode_float(&float_input, &mut float_output)
+            .unwrap();
+        assert!(float_len > 0);
+
+        encoder.reset_state().unwrap();
+        let mut repeated_output = [0; 4000];
+        assert_eq!(encoder.encode(&input, &mut repeated_output).unwrap(), len);
+        assert_eq!(encoder.final_range().unwrap(), final_range);
+        assert_eq!(repeated_output[..len], output[..len]);
+    }
+
+    #[test]
+    fn build_valid_mapping() {
+        // Quadraphonic from two coupled streams.
+        let mapping = MappingBuilder::new(4, 2, 2)
+            .channel(0)
+            .channel(1)
+            .channel(2)
+            .channel(3)
+            .build();
+        assert_matches!(mapping.as_deref(), Ok([0, 1, 2, 3]));
+
+        let mapping = MappingBuilder::new(2, 1, 0).channel(0).silent().build();
+        assert_matches!(mapping.as_deref(), Ok([0, 255]));
+    }
+
+    #[test]
+    fn build_malformed_mapping() {
+        assert_matches!(
+            MappingBuilder::new(3, 2, 1).channel(0).channel(1).build(),
+            Err(Error::MappingExpectedLen(3))
+        );
+
+        assert_matches!(
+            MappingBuilder::new(2, 1, 0)
+                .channel(0)
+                .channel(1)
+                .channel(0)
+                .build(),
+            Err(Error::MappingExpectedLen(2))
+        );
+
+        assert_matches!(
+            MappingBuilder::new(2, 1, 0).channel(0).channel(1).build(),
+            Err(Error::InvalidMapping(1))
+        );
+    }
+
+    #[test]
+    fn ms_conceal_surround() {
+        const FRAME_SIZE: usize = 960;
+        const SURROUND_20MS: usize = FRAME_SIZE * 6;
+
+        // 5.1 surround from two coupled streams for front and rear,
+        // followed by mono streams for center and LFE.
+        let mapping = [0, 4, 1, 2, 3, 5];
+        let encoder =
+            MsEncoder::new(SampleRate::Hz48000, 6, 4, 2, &mapping, Application::Audio).unwrap();
+        let mut decoder = MsDecoder::new(SampleRate::Hz48000, 6, 4, 2, &mapping).unwrap();
+
+        let input = (0..SURROUND_20MS)
+            .map(|i| ((i * 131) % 8192) as i16 - 4096)
+            .collect::<Vec<_>>();
+        let mut packet_buffer = [0; 4000];
+        let len = encoder.encode(&input, &mut packet_buffer).unwrap();
+
+        let mut output = vec![0_i16; SURROUND_20MS];
+        decoder
+            .decode(
+                Some(Packet::try_from(&packet_buffer[..len]).unwrap()),
+                MutSignals::try_from(&mut output).unwrap(),
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(decoder.conceal(FRAME_SIZE).unwrap().len(), SURROUND_20MS);
+        assert_eq!(
+            decoder.conceal(FRAME_SIZE / 2).unwrap().len(),
+            SURROUND_20MS / 2
+        );
+
+        assert_matches!(
+            decoder.conceal(100),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+    }
+
+    #[test]
+    fn ms_round_trip() {
+        const FRAME_SIZE: usize = 960;
+        const QUAD_20MS: usize = FRAME_SIZE * 4;
+
+        let mapping = [0, 1, 2, 3];
+        let encoder =
+            MsEncoder::new(SampleRate::Hz48000, 4, 2, 2, &mapping, Application::Audio).unwrap();
+        let mut decoder = MsDecoder::new(SampleRate::Hz48000, 4, 2, 2, &mapping).unwrap();
+
+        assert_matches!(
+            MsDecoder::new(SampleRate::Hz48000, 4, 2, 2, &mapping[..2]),
+            Err(Error::MappingExpectedLen(4))
+        );
+
+        let input = (0..QUAD_20MS)
+            .map(|i| ((i * 131) % 8192) as i16 - 4096)
+            .collect::<Vec<_>>();
+        let mut packet_buffer = [0; 4000];
+        let len = encoder.encode(&input, &mut packet_buffer).unwrap();
+        let packet = Packet::try_from(&packet_buffer[..len]).unwrap();
+
+        let mut output = vec![0_i16; QUAD_20MS];
+        let samples = decoder
+            .decode(
+                Some(packet),
+                MutSignals::try_from(&mut output).unwrap(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(samples, FRAME_SIZE);
+        assert_eq!(
+            decoder.final_range().unwrap(),
+            encoder.final_range().unwrap()
+        );
+
+        let mut float_output = vec![0_f32; QUAD_20MS];
+        let concealed = decoder
+            .decode_float(
+                None,
+                MutSignals::try_from(&mut float_output).unwrap(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(concealed, FRAME_SIZE);
+
+        let mut misaligned = vec![0_i16; QUAD_20MS - 1];
+        assert_matches!(
+            decoder.decode(
+                Some(packet),
+                MutSignals::try_from(&mut misaligned).unwrap(),
+                false
+            ),
+            Err(Error::MappingExpectedLen(4))
+        );
+    }
+}
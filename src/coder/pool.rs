@@ -0,0 +1,246 @@
+use super::{Decoder, Encoder, GenericCtl};
+use crate::{Application, Channels, Result, SampleRate};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::{Deref, DerefMut},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// Configuration identifying interchangeable encoders.
+pub type EncoderKey = (SampleRate, Channels, Application);
+
+/// Configuration identifying interchangeable decoders.
+pub type DecoderKey = (SampleRate, Channels);
+
+/// Pool of [`Encoder`]s reused across streams of the same configuration.
+///
+/// [`Encoder`]: struct.Encoder.html
+pub type EncoderPool = Pool<EncoderKey, Encoder>;
+
+/// Pool of [`Decoder`]s reused across streams of the same configuration.
+///
+/// [`Decoder`]: struct.Decoder.html
+pub type DecoderPool = Pool<DecoderKey, Decoder>;
+
+/// Keeps idle coders keyed by their configuration to save creating a new
+/// one via Opus for every stream.
+///
+/// Coders are handed out as [`Pooled`] guards, returning the coder to the
+/// pool when dropped.
+///
+/// [`Pooled`]: struct.Pooled.html
+#[derive(Debug)]
+pub struct Pool<K, C> {
+    idle: Mutex<HashMap<K, Vec<C>>>,
+}
+
+impl<K: Eq + Hash, C> Default for Pool<K, C> {
+    fn default() -> Self {
+        Self {
+            idle: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash, C> Pool<K, C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the amount of idle coders of all configurations.
+    pub fn idle_len(&self) -> usize {
+        self.lock().values().map(Vec::len).sum()
+    }
+
+    /// The map is consistent after every operation, a panic while holding
+    /// the lock cannot leave it half-updated.
+    fn lock(&self) -> MutexGuard<'_, HashMap<K, Vec<C>>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn acquire_with(
+        &self,
+        key: K,
+        key_of: fn(&C) -> Result<K>,
+        create: impl FnOnce() -> Result<C>,
+    ) -> Result<Pooled<'_, K, C>>
+    where
+        C: GenericCtl,
+    {
+        let idle = self.lock().get_mut(&key).and_then(Vec::pop);
+
+        let coder = match idle {
+            Some(coder) => coder,
+            None => create()?,
+        };
+
+        Ok(Pooled {
+            pool: self,
+            key: Some(key),
+            key_of,
+            coder: Some(coder),
+        })
+    }
+}
+
+impl Pool<EncoderKey, Encoder> {
+    /// Hands out an idle encoder of the configuration or creates a new one
+    /// if there is none.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to create a new encoder.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn acquire(
+        &self,
+        sample_rate: SampleRate,
+        channels: Channels,
+        mode: Application,
+    ) -> Result<Pooled<'_, EncoderKey, Encoder>> {
+        self.acquire_with((sample_rate, channels, mode), encoder_key, || {
+            Encoder::new(sample_rate, channels, mode)
+        })
+    }
+}
+
+/// Reads the configuration the encoder currently runs with, which differs
+/// from the acquired one after e.g. [`Encoder::reinit`].
+///
+/// [`Encoder::reinit`]: struct.Encoder.html#method.reinit
+fn encoder_key(encoder: &Encoder) -> Result<EncoderKey> {
+    Ok((
+        encoder.sample_rate()?,
+        encoder.channels(),
+        encoder.application()?,
+    ))
+}
+
+impl Pool<DecoderKey, Decoder> {
+    /// Hands out an idle decoder of the configuration or creates a new one
+    /// if there is none.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to create a new decoder.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn acquire(
+        &self,
+        sample_rate: SampleRate,
+        channels: Channels,
+    ) -> Result<Pooled<'_, DecoderKey, Decoder>> {
+        self.acquire_with((sample_rate, channels), decoder_key, || {
+            Decoder::new(sample_rate, channels)
+        })
+    }
+}
+
+/// Reads the configuration the decoder currently runs with, which differs
+/// from the acquired one after e.g. [`Decoder::reinit`].
+///
+/// [`Decoder::reinit`]: struct.Decoder.html#method.reinit
+fn decoder_key(decoder: &Decoder) -> Result<DecoderKey> {
+    Ok((decoder.sample_rate()?, decoder.channels()))
+}
+
+/// A coder borrowed from a [`Pool`], dereferencing to the coder.
+///
+/// On drop, the coder's state is reset and it is returned to the pool.
+/// If resetting fails or the coder's sample rate, channels or application
+/// no longer match the configuration it was acquired with, the coder is
+/// destroyed instead.
+///
+/// **Warning**:
+/// Resetting clears the stream's state only, settings changed via CTLs,
+/// such as the bitrate, stay with the coder and carry over to the next
+/// stream acquiring it.
+///
+/// [`Pool`]: struct.Pool.html
+#[derive(Debug)]
+pub struct Pooled<'a, K: Eq + Hash, C: GenericCtl> {
+    pool: &'a Pool<K, C>,
+    key: Option<K>,
+    key_of: fn(&C) -> Result<K>,
+    coder: Option<C>,
+}
+
+impl<K: Eq + Hash, C: GenericCtl> Deref for Pooled<'_, K, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.coder.as_ref().expect("Coder is only taken on drop.")
+    }
+}
+
+impl<K: Eq + Hash, C: GenericCtl> DerefMut for Pooled<'_, K, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.coder.as_mut().expect("Coder is only taken on drop.")
+    }
+}
+
+impl<K: Eq + Hash, C: GenericCtl> Drop for Pooled<'_, K, C> {
+    fn drop(&mut self) {
+        if let (Some(key), Some(mut coder)) = (self.key.take(), self.coder.take()) {
+            let unchanged = (self.key_of)(&coder).ok().as_ref() == Some(&key);
+
+            if unchanged && coder.reset_state().is_ok() {
+                self.pool.lock().entry(key).or_default().push(coder);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecoderPool, EncoderPool};
+    use crate::{coder::GenericCtl, Application, Channels, SampleRate};
+
+    #[test]
+    fn acquire_reuses_encoder() {
+        let pool = EncoderPool::new();
+
+        let first = pool
+            .acquire(SampleRate::Hz48000, Channels::Stereo, Application::Audio)
+            .unwrap();
+        let pointer = first.pointer;
+        drop(first);
+        assert_eq!(pool.idle_len(), 1);
+
+        let second = pool
+            .acquire(SampleRate::Hz48000, Channels::Stereo, Application::Audio)
+            .unwrap();
+        assert_eq!(second.pointer, pointer);
+        assert_eq!(pool.idle_len(), 0);
+
+        let other = pool
+            .acquire(SampleRate::Hz48000, Channels::Stereo, Application::Voip)
+            .unwrap();
+        assert_ne!(other.pointer, pointer);
+    }
+
+    #[test]
+    fn acquire_reuses_decoder() {
+        let pool = DecoderPool::new();
+
+        let first = pool.acquire(SampleRate::Hz16000, Channels::Mono).unwrap();
+        let pointer = first.pointer;
+        drop(first);
+
+        let second = pool.acquire(SampleRate::Hz16000, Channels::Mono).unwrap();
+        assert_eq!(second.pointer, pointer);
+    }
+
+    #[test]
+    fn reconfigured_coder_is_not_returned() {
+        let pool = DecoderPool::new();
+
+        let mut first = pool.acquire(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        first.reinit(SampleRate::Hz16000, Channels::Mono).unwrap();
+        drop(first);
+        assert_eq!(pool.idle_len(), 0);
+
+        let second = pool.acquire(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        assert_eq!(second.sample_rate().unwrap(), SampleRate::Hz48000);
+        assert_eq!(second.channels(), Channels::Stereo);
+    }
+}
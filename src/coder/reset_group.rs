@@ -0,0 +1,93 @@
+use super::{Decoder, GenericCtl};
+use crate::{softclip::SoftClip, Result};
+
+/// Bundles a [`Decoder`] with the [`SoftClip`] applied to its output.
+///
+/// Both carry state across calls, resetting only one of them mid-stream
+/// lets the stale state of the other leak into the new stream.
+/// [`reset`] clears both at once.
+///
+/// [`Decoder`]: struct.Decoder.html
+/// [`SoftClip`]: ../softclip/struct.SoftClip.html
+/// [`reset`]: struct.ResetGroup.html#method.reset
+#[derive(Debug)]
+pub struct ResetGroup {
+    decoder: Decoder,
+    soft_clip: SoftClip,
+}
+
+impl ResetGroup {
+    /// Creates a new group, the soft-clip uses the decoder's channels.
+    pub fn new(decoder: Decoder) -> Self {
+        let soft_clip = SoftClip::new(decoder.channels());
+
+        Self { decoder, soft_clip }
+    }
+
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    pub fn decoder_mut(&mut self) -> &mut Decoder {
+        &mut self.decoder
+    }
+
+    pub fn soft_clip(&self) -> &SoftClip {
+        &self.soft_clip
+    }
+
+    pub fn soft_clip_mut(&mut self) -> &mut SoftClip {
+        &mut self.soft_clip
+    }
+
+    /// Resets the decoder's state and clears the soft-clip's memory.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to reset the decoder, the
+    /// soft-clip is left untouched in this case.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn reset(&mut self) -> Result<()> {
+        self.decoder.reset_state()?;
+        self.soft_clip.reset();
+
+        Ok(())
+    }
+
+    /// Splits the group into its decoder and soft-clip.
+    pub fn into_inner(self) -> (Decoder, SoftClip) {
+        (self.decoder, self.soft_clip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResetGroup;
+    use crate::{coder::Decoder, softclip::SoftClip, Channels, SampleRate};
+    use std::convert::TryInto;
+
+    #[test]
+    fn reset_clears_decoder_and_soft_clip() {
+        let decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut group = ResetGroup::new(decoder);
+
+        let mut output = vec![0_f32; 1920];
+        group
+            .decoder_mut()
+            .decode_float(None, (&mut output).try_into().unwrap(), false)
+            .unwrap();
+        assert_eq!(group.decoder().last_packet_duration().unwrap(), 960);
+
+        let mut signals = vec![5.0, -5000.3453];
+        group
+            .soft_clip_mut()
+            .apply((&mut signals).try_into().unwrap())
+            .unwrap();
+        assert_ne!(group.soft_clip(), &SoftClip::new(Channels::Stereo));
+
+        group.reset().unwrap();
+
+        assert_eq!(group.decoder().last_packet_duration().unwrap(), 0);
+        assert_eq!(group.soft_clip(), &SoftClip::new(Channels::Stereo));
+    }
+}
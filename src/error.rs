@@ -1,7 +1,8 @@
-use crate::ffi;
+use crate::{ffi, Channels, SampleRate};
 use std::{
     error::Error as StdError,
     fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error as IoError, ErrorKind},
 };
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -35,6 +36,14 @@ pub enum Error {
     ///
     /// [`Channels`]: ../enum.Channels.html
     InvalidChannels(i32),
+    /// A value failed to match a documented [`FrameDuration`].
+    ///
+    /// [`FrameDuration`]: ../enum.FrameDuration.html
+    InvalidFrameDuration(i32),
+    /// A string failed to name a documented [`Channels`].
+    ///
+    /// [`Channels`]: ../enum.Channels.html
+    InvalidChannelsName,
     /// An error returned from Opus containing an [`ErrorCode`] describing
     /// the cause.
     Opus(ErrorCode),
@@ -48,12 +57,63 @@ pub enum Error {
     PacketTooLarge,
     /// A `Vec` representing a mapping exceeded the expected value.
     MappingExpectedLen(usize),
+    /// A channel mapping's entry referenced a decoded channel that does not
+    /// exist.
+    InvalidMapping(u8),
+    /// Signals did not contain the expected amount of samples.
+    SignalsExpectedLen(usize),
+    /// Opus failed to decode the packet at the contained index of a sequence
+    /// of packets, the [`ErrorCode`] describes the cause.
+    ///
+    /// [`ErrorCode`]: enum.ErrorCode.html
+    DecodePacket(usize, ErrorCode),
+    /// An encoder's and a decoder's sample rates, in this order, differ.
+    SampleRateMismatch(SampleRate, SampleRate),
+    /// An encoder's and a decoder's channels, in this order, differ.
+    ChannelsMismatch(Channels, Channels),
+    /// An output of `got` bytes cannot hold the `needed` bytes.
+    OutputTooSmall { needed: usize, got: usize },
+}
+
+impl Error {
+    /// Returns `true` if retrying the failed call with adjusted arguments,
+    /// e.g. a bigger output buffer, may succeed.
+    ///
+    /// Only Opus' [`ErrorCode`]s can be recoverable, the remaining variants
+    /// describe invalid values that fail again when passed again.
+    ///
+    /// [`ErrorCode`]: enum.ErrorCode.html
+    #[must_use]
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Error::Opus(error_code) | Error::DecodePacket(_, error_code) => {
+                error_code.is_recoverable()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the [`ErrorCode`] Opus reported, if the error originates from
+    /// Opus.
+    ///
+    /// This includes the code of a failed packet in a sequence, see
+    /// [`DecodePacket`].
+    ///
+    /// [`ErrorCode`]: enum.ErrorCode.html
+    /// [`DecodePacket`]: enum.Error.html#variant.DecodePacket
+    #[must_use]
+    pub fn opus_code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::Opus(error_code) | Error::DecodePacket(_, error_code) => Some(*error_code),
+            _ => None,
+        }
+    }
 }
 
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
-            Error::Opus(err) => Some(err),
+            Error::Opus(err) | Error::DecodePacket(_, err) => Some(err),
             _ => None,
         }
     }
@@ -68,12 +128,36 @@ impl Display for Error {
             Error::InvalidComplexity(complexity) => write!(f, "Invalid Complexity: {}", complexity),
             Error::InvalidSampleRate(rate) => write!(f, "Invalid Sample Rate: {}", rate),
             Error::InvalidChannels(channels) => write!(f, "Invalid Channels: {}", channels),
-            Error::Opus(error_code) => write!(f, "{}", error_code),
+            Error::InvalidChannelsName => f.write_str("Invalid Channels name"),
+            Error::InvalidFrameDuration(duration) => {
+                write!(f, "Invalid Frame Duration: {}", duration)
+            }
+            Error::Opus(error_code) => error_code.fmt(f),
             Error::EmptyPacket => f.write_str("Passed packet contained no elements"),
             Error::SignalsTooLarge => f.write_str("Signals' length exceeded `i32::MAX`"),
             Error::PacketTooLarge => f.write_str("Packet's length exceeded `i32::MAX`"),
             Error::InvalidBitrate(rate) => write!(f, "Invalid Bitrate: {}", rate),
             Error::MappingExpectedLen(len) => write!(f, "Wrong channel length, expected: {}", len),
+            Error::InvalidMapping(entry) => write!(f, "Invalid channel mapping entry: {}", entry),
+            Error::SignalsExpectedLen(len) => write!(f, "Wrong signals length, expected: {}", len),
+            Error::DecodePacket(index, error_code) => {
+                write!(f, "Decoding packet {} failed: {}", index, error_code)
+            }
+            Error::SampleRateMismatch(encoder, decoder) => write!(
+                f,
+                "Sample rates differ, encoder: {:?}, decoder: {:?}",
+                encoder, decoder
+            ),
+            Error::ChannelsMismatch(encoder, decoder) => write!(
+                f,
+                "Channels differ, encoder: {:?}, decoder: {:?}",
+                encoder, decoder
+            ),
+            Error::OutputTooSmall { needed, got } => write!(
+                f,
+                "Output too small, needed: {} bytes, got: {} bytes",
+                needed, got
+            ),
         }
     }
 }
@@ -84,6 +168,30 @@ impl From<ErrorCode> for Error {
     }
 }
 
+/// Wraps the error into an [`io::Error`], allowing `?` in functions
+/// returning [`io::Result`].
+///
+/// A too small buffer maps to [`ErrorKind::InvalidInput`], a failed
+/// allocation to [`ErrorKind::OutOfMemory`], everything else to
+/// [`ErrorKind::Other`]. The message stays the same.
+///
+/// [`io::Error`]: std::io::Error
+/// [`io::Result`]: std::io::Result
+/// [`ErrorKind::InvalidInput`]: std::io::ErrorKind::InvalidInput
+/// [`ErrorKind::OutOfMemory`]: std::io::ErrorKind::OutOfMemory
+/// [`ErrorKind::Other`]: std::io::ErrorKind::Other
+impl From<Error> for IoError {
+    fn from(error: Error) -> IoError {
+        let kind = match error.opus_code() {
+            Some(ErrorCode::BufferTooSmall) => ErrorKind::InvalidInput,
+            Some(ErrorCode::AllocFail) => ErrorKind::OutOfMemory,
+            _ => ErrorKind::Other,
+        };
+
+        IoError::new(kind, error)
+    }
+}
+
 #[repr(i32)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum ErrorCode {
@@ -100,6 +208,20 @@ pub enum ErrorCode {
     Unknown = 0,
 }
 
+impl ErrorCode {
+    /// Returns `true` if the error is transient and retrying the call with
+    /// adjusted arguments may succeed.
+    ///
+    /// This is only the case for [`BufferTooSmall`], retrying with a bigger
+    /// buffer. All other codes are fatal for the given input or instance.
+    ///
+    /// [`BufferTooSmall`]: #variant.BufferTooSmall
+    #[must_use]
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, ErrorCode::BufferTooSmall)
+    }
+}
+
 impl Display for ErrorCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         let s = match self {
@@ -115,7 +237,7 @@ impl Display for ErrorCode {
             }
         };
 
-        write!(f, "{}", s)
+        f.write_str(s)
     }
 }
 
@@ -144,3 +266,223 @@ pub fn try_map_opus_error(ffi_return_value: i32) -> Result<i32> {
         _ => Ok(ffi_return_value),
     }
 }
+
+/// Maps the `opus_code` of an `opus_*_create` call that failed to return a
+/// usable pointer.
+///
+/// Opus signals a failed allocation with a null pointer, a null pointer
+/// paired with `OPUS_OK` is therefore treated as allocation failure too.
+pub(crate) fn create_error(opus_code: i32) -> Error {
+    match opus_code {
+        ffi::OPUS_OK => ErrorCode::AllocFail.into(),
+        _ => ErrorCode::from(opus_code).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_error, Error, ErrorCode};
+    use crate::{Channels, SampleRate};
+
+    #[test]
+    fn null_pointer_without_error_code_is_alloc_fail() {
+        use crate::ffi;
+
+        assert_eq!(
+            create_error(ffi::OPUS_OK).opus_code(),
+            Some(ErrorCode::AllocFail)
+        );
+        assert_eq!(
+            create_error(ffi::OPUS_BAD_ARG).opus_code(),
+            Some(ErrorCode::BadArgument)
+        );
+    }
+
+    #[test]
+    fn convert_into_io_error() {
+        use std::io::{Error as IoError, ErrorKind};
+
+        let io_error = IoError::from(Error::Opus(ErrorCode::BufferTooSmall));
+        assert_eq!(io_error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(io_error.to_string(), "Passed buffer was too small");
+
+        let io_error = IoError::from(Error::DecodePacket(1, ErrorCode::AllocFail));
+        assert_eq!(io_error.kind(), ErrorKind::OutOfMemory);
+
+        let io_error = IoError::from(Error::Opus(ErrorCode::InvalidPacket));
+        assert_eq!(io_error.kind(), ErrorKind::Other);
+
+        let io_error = IoError::from(Error::EmptyPacket);
+        assert_eq!(io_error.kind(), ErrorKind::Other);
+        assert_eq!(io_error.to_string(), Error::EmptyPacket.to_string());
+    }
+
+    #[test]
+    fn source_downcasts_to_error_code() {
+        use std::error::Error as _;
+
+        let error = Error::Opus(ErrorCode::InvalidPacket);
+        let source = error.source().expect("Opus errors have a source.");
+        assert_eq!(
+            source.downcast_ref::<ErrorCode>(),
+            Some(&ErrorCode::InvalidPacket)
+        );
+
+        let error = Error::DecodePacket(3, ErrorCode::BufferTooSmall);
+        assert_eq!(
+            error
+                .source()
+                .and_then(|source| source.downcast_ref::<ErrorCode>()),
+            Some(&ErrorCode::BufferTooSmall)
+        );
+
+        assert!(Error::EmptyPacket.source().is_none());
+    }
+
+    #[test]
+    fn boxed_error_keeps_message() {
+        let boxed = Box::<dyn std::error::Error>::from(Error::Opus(ErrorCode::BadArgument));
+
+        assert_eq!(
+            boxed.to_string(),
+            "Passed argument violated Opus' specified requirements"
+        );
+    }
+
+    #[test]
+    fn error_code_is_recoverable() {
+        assert!(ErrorCode::BufferTooSmall.is_recoverable());
+
+        assert!(!ErrorCode::BadArgument.is_recoverable());
+        assert!(!ErrorCode::InternalError.is_recoverable());
+        assert!(!ErrorCode::InvalidPacket.is_recoverable());
+        assert!(!ErrorCode::Unimplemented.is_recoverable());
+        assert!(!ErrorCode::InvalidState.is_recoverable());
+        assert!(!ErrorCode::AllocFail.is_recoverable());
+        assert!(!ErrorCode::Unknown.is_recoverable());
+    }
+
+    #[test]
+    fn error_is_recoverable() {
+        assert!(Error::Opus(ErrorCode::BufferTooSmall).is_recoverable());
+        assert!(Error::DecodePacket(1, ErrorCode::BufferTooSmall).is_recoverable());
+
+        assert!(!Error::Opus(ErrorCode::AllocFail).is_recoverable());
+        assert!(!Error::DecodePacket(1, ErrorCode::InvalidPacket).is_recoverable());
+        assert!(!Error::EmptyPacket.is_recoverable());
+        assert!(!Error::SignalsTooLarge.is_recoverable());
+        assert!(!Error::InvalidChannels(3).is_recoverable());
+    }
+
+    #[test]
+    fn error_opus_code() {
+        assert_eq!(
+            Error::Opus(ErrorCode::BufferTooSmall).opus_code(),
+            Some(ErrorCode::BufferTooSmall)
+        );
+        assert_eq!(
+            Error::DecodePacket(2, ErrorCode::InvalidPacket).opus_code(),
+            Some(ErrorCode::InvalidPacket)
+        );
+
+        assert_eq!(Error::EmptyPacket.opus_code(), None);
+        assert_eq!(Error::InvalidChannels(3).opus_code(), None);
+        assert_eq!(Error::InvalidSampleRate(44100).opus_code(), None);
+    }
+
+    #[test]
+    fn display_error_codes() {
+        let cases = [
+            (
+                ErrorCode::BadArgument,
+                "Passed argument violated Opus' specified requirements",
+            ),
+            (ErrorCode::BufferTooSmall, "Passed buffer was too small"),
+            (
+                ErrorCode::InternalError,
+                "Internal error inside Opus occured",
+            ),
+            (
+                ErrorCode::InvalidPacket,
+                "Opus received a packet violating requirements",
+            ),
+            (
+                ErrorCode::Unimplemented,
+                "Unimplemented code branch was attempted to be executed",
+            ),
+            (
+                ErrorCode::InvalidState,
+                "Opus-type instance is in an invalid state",
+            ),
+            (ErrorCode::AllocFail, "Opus was unable to allocate memory"),
+            (
+                ErrorCode::Unknown,
+                "Opus returned a non-negative error, this might be a Audiopus or Opus bug",
+            ),
+        ];
+
+        for (error_code, message) in &cases {
+            assert_eq!(error_code.to_string(), *message);
+        }
+    }
+
+    #[test]
+    fn display_errors() {
+        let cases = [
+            (Error::InvalidApplication, "Invalid Application"),
+            (Error::InvalidBandwidth(1), "Invalid Bandwitdh: 1"),
+            (Error::InvalidBitrate(-2), "Invalid Bitrate: -2"),
+            (Error::InvalidSignal(3), "Invalid Signal: 3"),
+            (Error::InvalidComplexity(11), "Invalid Complexity: 11"),
+            (
+                Error::InvalidSampleRate(44100),
+                "Invalid Sample Rate: 44100",
+            ),
+            (Error::InvalidChannels(3), "Invalid Channels: 3"),
+            (Error::InvalidChannelsName, "Invalid Channels name"),
+            (
+                Error::InvalidFrameDuration(20),
+                "Invalid Frame Duration: 20",
+            ),
+            (
+                Error::Opus(ErrorCode::BufferTooSmall),
+                "Passed buffer was too small",
+            ),
+            (Error::EmptyPacket, "Passed packet contained no elements"),
+            (
+                Error::SignalsTooLarge,
+                "Signals' length exceeded `i32::MAX`",
+            ),
+            (Error::PacketTooLarge, "Packet's length exceeded `i32::MAX`"),
+            (
+                Error::MappingExpectedLen(2),
+                "Wrong channel length, expected: 2",
+            ),
+            (Error::InvalidMapping(7), "Invalid channel mapping entry: 7"),
+            (
+                Error::SignalsExpectedLen(960),
+                "Wrong signals length, expected: 960",
+            ),
+            (
+                Error::DecodePacket(4, ErrorCode::InvalidPacket),
+                "Decoding packet 4 failed: Opus received a packet violating requirements",
+            ),
+            (
+                Error::SampleRateMismatch(SampleRate::Hz48000, SampleRate::Hz16000),
+                "Sample rates differ, encoder: Hz48000, decoder: Hz16000",
+            ),
+            (
+                Error::ChannelsMismatch(Channels::Stereo, Channels::Mono),
+                "Channels differ, encoder: Stereo, decoder: Mono",
+            ),
+            (
+                Error::OutputTooSmall { needed: 7, got: 3 },
+                "Output too small, needed: 7 bytes, got: 3 bytes",
+            ),
+        ];
+
+        for (error, message) in &cases {
+            assert_eq!(error.to_string(), *message);
+        }
+    }
+}
@@ -21,6 +21,14 @@
 //! [`MutSignals`] wraps around a generic buffer and represents Opus' output.
 //! E.g. when encoding, Opus will fill the buffer with the encoded data.
 //!
+//! With the `serde` feature enabled, the configuration enums such as
+//! [`Bitrate`] and [`SampleRate`] serialize as the `i32` values Opus uses
+//! and deserialize via their [`TryFrom`] implementations, rejecting values
+//! Opus does not know.
+//!
+//! With the `proptest` feature enabled, the configuration enums implement
+//! [`Arbitrary`], generating only values Opus accepts.
+//!
 //! Audiopus aims to never panic or crash when interacting with Opus,
 //! if either occurs, consider this a bug and please report it on the GitHub!
 //!
@@ -29,6 +37,9 @@
 //! [`MutSignals`]: crate::MutSignals
 //! [`TryFrom`]: std::convert::TryFrom
 //! [`Result`]: std::result::Result
+//! [`Bitrate`]: crate::Bitrate
+//! [`SampleRate`]: crate::SampleRate
+//! [`Arbitrary`]: https://docs.rs/proptest/1/proptest/arbitrary/trait.Arbitrary.html
 //!
 #![deny(rust_2018_idioms)]
 #![deny(clippy::all)]
@@ -38,15 +49,22 @@
 // TODO: Document all public items.
 // #![deny(missing_docs)]
 
+#[cfg(feature = "proptest")]
+mod arbitrary;
 pub mod coder;
 pub mod error;
 pub mod packet;
+pub mod pcm;
 pub mod repacketizer;
 pub mod softclip;
 
 use std::{
+    cmp::Ordering,
     convert::{TryFrom, TryInto},
     ffi::CStr,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{Add, Sub},
+    str::FromStr,
 };
 
 pub use crate::error::{Error, ErrorCode, Result};
@@ -54,6 +72,11 @@ pub use audiopus_sys as ffi;
 
 #[repr(i32)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
 pub enum Signal {
     Auto = ffi::OPUS_AUTO,
     Voice = ffi::OPUS_SIGNAL_VOICE,
@@ -73,7 +96,99 @@ impl TryFrom<i32> for Signal {
     }
 }
 
+impl From<Signal> for i32 {
+    fn from(signal: Signal) -> i32 {
+        signal as i32
+    }
+}
+
+impl Signal {
+    /// Guesses whether `pcm` (interleaved if 2 channels) contains voice or
+    /// music, e.g. to pick a value for [`Encoder::set_signal`].
+    ///
+    /// **Info**:
+    /// This is a best-effort heuristic, not a classifier. The signal is cut
+    /// into blocks of 256 samples per channel, speech alternates between
+    /// syllables and pauses and between voiced and unvoiced sounds, making
+    /// the blocks' energy and zero-crossing rate fluctuate strongly while
+    /// music tends to stay steady.
+    /// Returns [`Signal::Auto`] when the fluctuation is inconclusive, the
+    /// signal is silent, shorter than 2048 samples per channel, or
+    /// `channels` is [`Channels::Auto`].
+    ///
+    /// [`Encoder::set_signal`]: coder/struct.Encoder.html#method.set_signal
+    /// [`Signal::Auto`]: enum.Signal.html#variant.Auto
+    /// [`Channels::Auto`]: enum.Channels.html#variant.Auto
+    #[must_use]
+    pub fn detect(pcm: &[i16], channels: Channels) -> Signal {
+        const BLOCK_FRAMES: usize = 256;
+        const MIN_BLOCKS: usize = 8;
+
+        let channels = match channels {
+            Channels::Mono => 1,
+            Channels::Stereo => 2,
+            Channels::Auto => return Signal::Auto,
+        };
+
+        let (energies, zero_crossing_rates): (Vec<f64>, Vec<f64>) = pcm
+            .chunks_exact(BLOCK_FRAMES * channels)
+            .map(|block| {
+                let mono = block
+                    .chunks_exact(channels)
+                    .map(|frame| frame.iter().map(|&sample| f64::from(sample)).sum::<f64>())
+                    .collect::<Vec<_>>();
+
+                let energy = mono.iter().map(|sample| sample * sample).sum::<f64>();
+                let crossings = mono
+                    .windows(2)
+                    .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+                    .count();
+
+                (energy, crossings as f64)
+            })
+            .unzip();
+
+        if energies.len() < MIN_BLOCKS || energies.iter().all(|&energy| energy == 0.0) {
+            return Signal::Auto;
+        }
+
+        let energy_variation = coefficient_of_variation(&energies);
+        let zero_crossing_variation = coefficient_of_variation(&zero_crossing_rates);
+
+        if energy_variation > 0.8 || zero_crossing_variation > 0.8 {
+            Signal::Voice
+        } else if energy_variation < 0.4 && zero_crossing_variation < 0.4 {
+            Signal::Music
+        } else {
+            Signal::Auto
+        }
+    }
+}
+
+/// Standard deviation relative to the mean, 0 for an all-zero `values`.
+fn coefficient_of_variation(values: &[f64]) -> f64 {
+    let len = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / len;
+
+    if mean == 0.0 {
+        return 0.0;
+    }
+
+    let variance = values
+        .iter()
+        .map(|value| (value - mean) * (value - mean))
+        .sum::<f64>()
+        / len;
+
+    variance.sqrt() / mean
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
 pub enum Bitrate {
     /// Explicit bitrate choice (in bits/second).
     BitsPerSecond(i32),
@@ -83,6 +198,52 @@ pub enum Bitrate {
     Auto,
 }
 
+impl Bitrate {
+    /// The lowest meaningful explicit bitrate in bits per second.
+    const MIN_BITS_PER_SECOND: i32 = 500;
+    /// The highest meaningful explicit bitrate in bits per second.
+    const MAX_BITS_PER_SECOND: i32 = 512_000;
+
+    fn map_bits(self, map: impl FnOnce(i32) -> i32) -> Bitrate {
+        match self {
+            Bitrate::BitsPerSecond(bits) => Bitrate::BitsPerSecond(
+                map(bits).clamp(Self::MIN_BITS_PER_SECOND, Self::MAX_BITS_PER_SECOND),
+            ),
+            Bitrate::Max | Bitrate::Auto => self,
+        }
+    }
+}
+
+/// Adds bits per second to a [`Bitrate::BitsPerSecond`], saturating within
+/// the meaningful range of 500 to 512000.
+/// [`Bitrate::Max`] and [`Bitrate::Auto`] stay unchanged.
+///
+/// [`Bitrate::BitsPerSecond`]: enum.Bitrate.html#variant.BitsPerSecond
+/// [`Bitrate::Max`]: enum.Bitrate.html#variant.Max
+/// [`Bitrate::Auto`]: enum.Bitrate.html#variant.Auto
+impl Add<i32> for Bitrate {
+    type Output = Bitrate;
+
+    fn add(self, bits: i32) -> Bitrate {
+        self.map_bits(|current| current.saturating_add(bits))
+    }
+}
+
+/// Subtracts bits per second from a [`Bitrate::BitsPerSecond`], saturating
+/// within the meaningful range of 500 to 512000.
+/// [`Bitrate::Max`] and [`Bitrate::Auto`] stay unchanged.
+///
+/// [`Bitrate::BitsPerSecond`]: enum.Bitrate.html#variant.BitsPerSecond
+/// [`Bitrate::Max`]: enum.Bitrate.html#variant.Max
+/// [`Bitrate::Auto`]: enum.Bitrate.html#variant.Auto
+impl Sub<i32> for Bitrate {
+    type Output = Bitrate;
+
+    fn sub(self, bits: i32) -> Bitrate {
+        self.map_bits(|current| current.saturating_sub(bits))
+    }
+}
+
 impl From<Bitrate> for i32 {
     fn from(bitrate: Bitrate) -> i32 {
         match bitrate {
@@ -109,7 +270,12 @@ impl TryFrom<i32> for Bitrate {
 /// Represents possible sample rates Opus can use.
 /// Values represent Hertz.
 #[repr(i32)]
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
 pub enum SampleRate {
     Hz8000 = 8000,
     Hz12000 = 12000,
@@ -118,6 +284,31 @@ pub enum SampleRate {
     Hz48000 = 48000,
 }
 
+impl SampleRate {
+    /// Returns every sample rate Opus supports, in ascending order.
+    #[must_use]
+    pub const fn all() -> &'static [SampleRate] {
+        &[
+            SampleRate::Hz8000,
+            SampleRate::Hz12000,
+            SampleRate::Hz16000,
+            SampleRate::Hz24000,
+            SampleRate::Hz48000,
+        ]
+    }
+
+    /// Returns the sample rates Opus supports, in ascending order.
+    ///
+    /// Opus supports exactly these five rates, this is an alias of [`all`]
+    /// meant for listing them at runtime, e.g. in a user interface.
+    ///
+    /// [`all`]: enum.SampleRate.html#method.all
+    #[must_use]
+    pub const fn supported() -> &'static [SampleRate] {
+        Self::all()
+    }
+}
+
 impl TryFrom<i32> for SampleRate {
     type Error = Error;
 
@@ -134,9 +325,20 @@ impl TryFrom<i32> for SampleRate {
     }
 }
 
+impl From<SampleRate> for i32 {
+    fn from(sample_rate: SampleRate) -> i32 {
+        sample_rate as i32
+    }
+}
+
 /// Represents possible application-types for Opus.
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
 pub enum Application {
     /// Best for most VoIP/videoconference applications where listening quality
     /// and intelligibility matter most.
@@ -145,6 +347,10 @@ pub enum Application {
     /// should be as close as possible to the input.
     Audio = ffi::OPUS_APPLICATION_AUDIO,
     /// Only use when lowest-achievable latency is what matters most.
+    ///
+    /// This mode disables the speech-optimised SILK layer, only the CELT
+    /// layer is used. CELT shines at higher sample rates, combined with the
+    /// narrowband sample rates of 8kHz and 12kHz the quality suffers.
     LowDelay = ffi::OPUS_APPLICATION_RESTRICTED_LOWDELAY,
 }
 
@@ -162,9 +368,20 @@ impl TryFrom<i32> for Application {
     }
 }
 
+impl From<Application> for i32 {
+    fn from(application: Application) -> i32 {
+        application as i32
+    }
+}
+
 /// Represents possible audio channels Opus can use.
 #[repr(i32)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
 pub enum Channels {
     /// Not supported when constructing encoders or decoders.
     Auto = ffi::OPUS_AUTO,
@@ -210,9 +427,44 @@ impl From<Channels> for i32 {
     }
 }
 
+impl Display for Channels {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(match self {
+            Channels::Auto => "auto",
+            Channels::Mono => "mono",
+            Channels::Stereo => "stereo",
+        })
+    }
+}
+
+impl FromStr for Channels {
+    type Err = Error;
+
+    /// Accepts the names `"auto"`, `"mono"`, and `"stereo"` ignoring ASCII
+    /// case, as well as the channel counts `"0"`, `"1"`, and `"2"`.
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+
+        Ok(match value {
+            "0" => Channels::Auto,
+            "1" => Channels::Mono,
+            "2" => Channels::Stereo,
+            _ if value.eq_ignore_ascii_case("auto") => Channels::Auto,
+            _ if value.eq_ignore_ascii_case("mono") => Channels::Mono,
+            _ if value.eq_ignore_ascii_case("stereo") => Channels::Stereo,
+            _ => return Err(Error::InvalidChannelsName),
+        })
+    }
+}
+
 /// Represents possible bandwidths of an Opus-stream.
 #[repr(i32)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
 pub enum Bandwidth {
     /// Pick the bandwidth automatically.
     Auto = ffi::OPUS_AUTO,
@@ -228,6 +480,37 @@ pub enum Bandwidth {
     Fullband = ffi::OPUS_BANDWIDTH_FULLBAND,
 }
 
+impl Bandwidth {
+    /// Ranks bandwidths by the audio frequencies they cover, `Auto` ranks
+    /// below all concrete bandwidths.
+    const fn rank(self) -> u8 {
+        match self {
+            Bandwidth::Auto => 0,
+            Bandwidth::Narrowband => 1,
+            Bandwidth::Mediumband => 2,
+            Bandwidth::Wideband => 3,
+            Bandwidth::Superwideband => 4,
+            Bandwidth::Fullband => 5,
+        }
+    }
+}
+
+/// Bandwidths are ordered from narrowest to widest, [`Bandwidth::Auto`] is
+/// the least.
+///
+/// [`Bandwidth::Auto`]: enum.Bandwidth.html#variant.Auto
+impl Ord for Bandwidth {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Bandwidth {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl TryFrom<i32> for Bandwidth {
     type Error = Error;
 
@@ -245,6 +528,70 @@ impl TryFrom<i32> for Bandwidth {
     }
 }
 
+impl From<Bandwidth> for i32 {
+    fn from(bandwidth: Bandwidth) -> i32 {
+        bandwidth as i32
+    }
+}
+
+/// Represents possible frame durations the encoder can be forced to use.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
+pub enum FrameDuration {
+    /// Select the frame duration from the length of the input signal.
+    Arg = ffi::OPUS_FRAMESIZE_ARG,
+    /// Use 2.5ms frames.
+    Ms2_5 = ffi::OPUS_FRAMESIZE_2_5_MS,
+    /// Use 5ms frames.
+    Ms5 = ffi::OPUS_FRAMESIZE_5_MS,
+    /// Use 10ms frames.
+    Ms10 = ffi::OPUS_FRAMESIZE_10_MS,
+    /// Use 20ms frames.
+    Ms20 = ffi::OPUS_FRAMESIZE_20_MS,
+    /// Use 40ms frames.
+    Ms40 = ffi::OPUS_FRAMESIZE_40_MS,
+    /// Use 60ms frames.
+    Ms60 = ffi::OPUS_FRAMESIZE_60_MS,
+    /// Use 80ms frames.
+    Ms80 = ffi::OPUS_FRAMESIZE_80_MS,
+    /// Use 100ms frames.
+    Ms100 = ffi::OPUS_FRAMESIZE_100_MS,
+    /// Use 120ms frames.
+    Ms120 = ffi::OPUS_FRAMESIZE_120_MS,
+}
+
+impl TryFrom<i32> for FrameDuration {
+    type Error = Error;
+
+    /// Fails if a value does not match Opus' specified frame duration-value.
+    fn try_from(value: i32) -> Result<Self> {
+        Ok(match value {
+            ffi::OPUS_FRAMESIZE_ARG => FrameDuration::Arg,
+            ffi::OPUS_FRAMESIZE_2_5_MS => FrameDuration::Ms2_5,
+            ffi::OPUS_FRAMESIZE_5_MS => FrameDuration::Ms5,
+            ffi::OPUS_FRAMESIZE_10_MS => FrameDuration::Ms10,
+            ffi::OPUS_FRAMESIZE_20_MS => FrameDuration::Ms20,
+            ffi::OPUS_FRAMESIZE_40_MS => FrameDuration::Ms40,
+            ffi::OPUS_FRAMESIZE_60_MS => FrameDuration::Ms60,
+            ffi::OPUS_FRAMESIZE_80_MS => FrameDuration::Ms80,
+            ffi::OPUS_FRAMESIZE_100_MS => FrameDuration::Ms100,
+            ffi::OPUS_FRAMESIZE_120_MS => FrameDuration::Ms120,
+            _ => return Err(Error::InvalidFrameDuration(value)),
+        })
+    }
+}
+
+impl From<FrameDuration> for i32 {
+    fn from(frame_duration: FrameDuration) -> i32 {
+        frame_duration as i32
+    }
+}
+
 /// A newtype wrapping around a mutable buffer. They represent mutably borrowed
 /// arguments that will be filled by Opus.
 /// E.g. you pass this to an encode-method and Opus encodes data into the
@@ -275,6 +622,14 @@ impl<'a, T> TryFrom<&'a mut Vec<T>> for MutSignals<'a, T> {
     }
 }
 
+impl<'a, T> TryFrom<&'a mut Box<[T]>> for MutSignals<'a, T> {
+    type Error = Error;
+
+    fn try_from(value: &'a mut Box<[T]>) -> Result<Self> {
+        (&mut **value).try_into()
+    }
+}
+
 impl<'a, T> MutSignals<'a, T> {
     pub fn as_mut_ptr(&mut self) -> *mut T {
         self.0.as_mut_ptr()
@@ -288,6 +643,16 @@ impl<'a, T> MutSignals<'a, T> {
     }
 }
 
+impl<'a, T: Default> MutSignals<'a, T> {
+    /// Fills the underlying buffer with `T::default()`, which is silence for
+    /// the signal types Opus operates on.
+    pub fn zero(&mut self) {
+        for signal in self.0.iter_mut() {
+            *signal = T::default();
+        }
+    }
+}
+
 /// Gets the libopus version string.
 ///
 /// Applications may look for the substring "-fixed" in the version string to
@@ -300,9 +665,61 @@ pub fn version() -> &'static str {
         .unwrap()
 }
 
+/// The libopus version, parsed from [`version`].
+///
+/// [`version`]: fn.version.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct OpusVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    /// The unparsed version string.
+    pub raw: &'static str,
+}
+
+impl OpusVersion {
+    /// Returns `true` if libopus has been built using fixed-point arithmetic
+    /// instead of floating-point.
+    #[must_use]
+    pub fn is_fixed_point(&self) -> bool {
+        self.raw.contains("-fixed")
+    }
+}
+
+/// Gets the libopus version parsed into its numbers.
+///
+/// Suffixes after the numbers, e.g. release candidates or git hashes, are
+/// ignored. Numbers missing from or unreadable in the version string are
+/// reported as zero.
+#[must_use]
+pub fn version_parsed() -> OpusVersion {
+    parse_version(version())
+}
+
+fn parse_version(raw: &'static str) -> OpusVersion {
+    let version = raw.trim_start_matches("libopus").trim_start();
+    let numbers_len = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+
+    let mut numbers = version[..numbers_len]
+        .split('.')
+        .map(|number| number.parse().unwrap_or(0));
+
+    OpusVersion {
+        major: numbers.next().unwrap_or(0),
+        minor: numbers.next().unwrap_or(0),
+        patch: numbers.next().unwrap_or(0),
+        raw,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ffi, version, Application, Error, Signal, TryFrom};
+    use super::{
+        ffi, parse_version, version, version_parsed, Application, Bandwidth, Bitrate, Channels,
+        Error, FrameDuration, MutSignals, SampleRate, Signal, TryFrom,
+    };
     use matches::assert_matches;
 
     #[test]
@@ -312,6 +729,32 @@ mod tests {
         version();
     }
 
+    #[test]
+    fn parse_versions() {
+        let release = parse_version("libopus 1.3.1");
+        assert_eq!((release.major, release.minor, release.patch), (1, 3, 1));
+        assert!(!release.is_fixed_point());
+
+        let fixed = parse_version("libopus 1.3.1-fixed");
+        assert_eq!((fixed.major, fixed.minor, fixed.patch), (1, 3, 1));
+        assert!(fixed.is_fixed_point());
+
+        let git = parse_version("libopus 1.3-beta-22-g1a2b3c4");
+        assert_eq!((git.major, git.minor, git.patch), (1, 3, 0));
+        assert_eq!(git.raw, "libopus 1.3-beta-22-g1a2b3c4");
+
+        let unknown = parse_version("libopus unknown");
+        assert_eq!((unknown.major, unknown.minor, unknown.patch), (0, 0, 0));
+
+        let overflowing = parse_version("libopus 1.70000.2");
+        assert_eq!(
+            (overflowing.major, overflowing.minor, overflowing.patch),
+            (1, 0, 2)
+        );
+
+        assert_eq!(version_parsed().raw, version());
+    }
+
     #[test]
     fn signal_try_from() {
         assert_matches!(Signal::try_from(ffi::OPUS_SIGNAL_MUSIC), Ok(Signal::Music));
@@ -320,6 +763,142 @@ mod tests {
         assert_matches!(Signal::try_from(0), Err(Error::InvalidSignal(0)));
     }
 
+    #[test]
+    fn signal_detect() {
+        let tone = (0..48000)
+            .map(|i| {
+                let t = f64::from(i) / 48000.0;
+                ((2.0 * std::f64::consts::PI * 440.0 * t).sin() * 10000.0) as i16
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(Signal::detect(&tone, Channels::Mono), Signal::Music);
+
+        // A voiced carrier with harmonics, gated into syllables four times
+        // per second.
+        let speech = (0..48000)
+            .map(|i| {
+                let t = f64::from(i) / 48000.0;
+                let syllables = (2.0 * std::f64::consts::PI * 4.0 * t).sin().max(0.0);
+                let voice = (1..=5)
+                    .map(|harmonic| {
+                        let frequency = 150.0 * f64::from(harmonic);
+                        (2.0 * std::f64::consts::PI * frequency * t).sin() / f64::from(harmonic)
+                    })
+                    .sum::<f64>();
+
+                (voice * syllables * syllables * 8000.0) as i16
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(Signal::detect(&speech, Channels::Mono), Signal::Voice);
+
+        let stereo_tone = tone
+            .iter()
+            .flat_map(|&sample| vec![sample, sample])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Signal::detect(&stereo_tone, Channels::Stereo),
+            Signal::Music
+        );
+
+        assert_eq!(
+            Signal::detect(&vec![0; 48000], Channels::Mono),
+            Signal::Auto
+        );
+        assert_eq!(Signal::detect(&tone[..1000], Channels::Mono), Signal::Auto);
+    }
+
+    #[test]
+    fn bitrate_arithmetic() {
+        let bitrate = Bitrate::BitsPerSecond(32000);
+
+        assert_eq!(bitrate + 8000, Bitrate::BitsPerSecond(40000));
+        assert_eq!(bitrate - 8000, Bitrate::BitsPerSecond(24000));
+        assert_eq!(bitrate + -8000, Bitrate::BitsPerSecond(24000));
+
+        assert_eq!(bitrate - 100_000, Bitrate::BitsPerSecond(500));
+        assert_eq!(bitrate + 1_000_000, Bitrate::BitsPerSecond(512_000));
+        assert_eq!(bitrate + i32::MAX, Bitrate::BitsPerSecond(512_000));
+
+        assert_eq!(Bitrate::Auto + 8000, Bitrate::Auto);
+        assert_eq!(Bitrate::Max - 8000, Bitrate::Max);
+    }
+
+    #[test]
+    fn supported_sample_rates() {
+        let supported = SampleRate::supported();
+
+        assert_eq!(supported.len(), 5);
+        assert_eq!(supported, SampleRate::all());
+        assert!(supported.windows(2).all(|w| (w[0] as i32) < (w[1] as i32)));
+    }
+
+    #[test]
+    fn order_bandwidths_and_sample_rates() {
+        assert!(Bandwidth::Narrowband < Bandwidth::Fullband);
+        assert!(Bandwidth::Auto < Bandwidth::Narrowband);
+        assert_eq!(
+            Bandwidth::Wideband.max(Bandwidth::Superwideband),
+            Bandwidth::Superwideband
+        );
+
+        let mut sample_rates = vec![
+            SampleRate::Hz48000,
+            SampleRate::Hz8000,
+            SampleRate::Hz24000,
+            SampleRate::Hz12000,
+            SampleRate::Hz16000,
+        ];
+        sample_rates.sort();
+        assert_eq!(sample_rates, SampleRate::all());
+    }
+
+    #[test]
+    fn sample_rate_try_from() {
+        let accepted = (-1..=200_000)
+            .filter_map(|hz| SampleRate::try_from(hz).ok())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            accepted,
+            [
+                SampleRate::Hz8000,
+                SampleRate::Hz12000,
+                SampleRate::Hz16000,
+                SampleRate::Hz24000,
+                SampleRate::Hz48000,
+            ]
+        );
+
+        assert_matches!(
+            SampleRate::try_from(44100),
+            Err(Error::InvalidSampleRate(44100))
+        );
+        assert_matches!(
+            SampleRate::try_from(i32::MAX),
+            Err(Error::InvalidSampleRate(i32::MAX))
+        );
+    }
+
+    #[test]
+    fn frame_duration_try_from() {
+        assert_matches!(
+            FrameDuration::try_from(ffi::OPUS_FRAMESIZE_ARG),
+            Ok(FrameDuration::Arg)
+        );
+        assert_matches!(
+            FrameDuration::try_from(ffi::OPUS_FRAMESIZE_2_5_MS),
+            Ok(FrameDuration::Ms2_5)
+        );
+        assert_matches!(
+            FrameDuration::try_from(ffi::OPUS_FRAMESIZE_120_MS),
+            Ok(FrameDuration::Ms120)
+        );
+        assert_matches!(
+            FrameDuration::try_from(20),
+            Err(Error::InvalidFrameDuration(20))
+        );
+    }
+
     #[test]
     fn application_try_from() {
         assert_matches!(
@@ -336,4 +915,75 @@ mod tests {
         );
         assert_matches!(Application::try_from(11), Err(Error::InvalidApplication));
     }
+
+    #[test]
+    fn channels_display_from_str() {
+        for channels in &[Channels::Auto, Channels::Mono, Channels::Stereo] {
+            assert_eq!(channels.to_string().parse::<Channels>(), Ok(*channels));
+        }
+
+        assert_eq!(Channels::Mono.to_string(), "mono");
+        assert_eq!(Channels::Stereo.to_string(), "stereo");
+        assert_eq!(Channels::Auto.to_string(), "auto");
+
+        assert_matches!("1".parse::<Channels>(), Ok(Channels::Mono));
+        assert_matches!("2".parse::<Channels>(), Ok(Channels::Stereo));
+        assert_matches!("0".parse::<Channels>(), Ok(Channels::Auto));
+        assert_matches!("Stereo".parse::<Channels>(), Ok(Channels::Stereo));
+
+        assert_matches!("3".parse::<Channels>(), Err(Error::InvalidChannelsName));
+        assert_matches!("".parse::<Channels>(), Err(Error::InvalidChannelsName));
+        assert_matches!(
+            "surround".parse::<Channels>(),
+            Err(Error::InvalidChannelsName)
+        );
+    }
+
+    #[test]
+    fn mut_signals_zero() {
+        let mut buffer = vec![1_i16, -2, 3, -4];
+        MutSignals::try_from(&mut buffer).unwrap().zero();
+        assert_eq!(buffer, [0; 4]);
+
+        let mut buffer = vec![0.5_f32, -0.25];
+        MutSignals::try_from(&mut buffer).unwrap().zero();
+        assert_eq!(buffer, [0.0; 2]);
+    }
+
+    #[cfg(feature = "serde")]
+    fn assert_serde_round_trip<T>(value: T, json: &str)
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + std::fmt::Debug + PartialEq,
+    {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        assert_serde_round_trip(Bitrate::BitsPerSecond(64000), "64000");
+        assert_serde_round_trip(Bitrate::Auto, "-1000");
+        assert_serde_round_trip(Bitrate::Max, "-1");
+        assert_serde_round_trip(SampleRate::Hz48000, "48000");
+        assert_serde_round_trip(Application::Voip, "2048");
+        assert_serde_round_trip(Channels::Stereo, "2");
+        assert_serde_round_trip(Channels::Auto, "-1000");
+        assert_serde_round_trip(Bandwidth::Wideband, "1103");
+        assert_serde_round_trip(Bandwidth::Auto, "-1000");
+        assert_serde_round_trip(Signal::Music, "3002");
+        assert_serde_round_trip(FrameDuration::Ms20, "5004");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_invalid_values() {
+        assert!(serde_json::from_str::<Bitrate>("0").is_err());
+        assert!(serde_json::from_str::<SampleRate>("44100").is_err());
+        assert!(serde_json::from_str::<Application>("1").is_err());
+        assert!(serde_json::from_str::<Channels>("3").is_err());
+        assert!(serde_json::from_str::<Bandwidth>("1106").is_err());
+        assert!(serde_json::from_str::<Signal>("3003").is_err());
+        assert!(serde_json::from_str::<FrameDuration>("4999").is_err());
+    }
 }
@@ -1,7 +1,8 @@
 use crate::{
-    error::try_map_opus_error, ffi, Bandwidth, Channels, Error, Result, SampleRate, TryFrom,
-    TryInto,
+    error::try_map_opus_error, ffi, Bandwidth, Channels, Error, ErrorCode, Result, SampleRate,
+    TryFrom, TryInto,
 };
+use std::time::Duration;
 
 fn packet_len_check(packet_buffer: &[u8]) -> Result<i32> {
     match packet_buffer {
@@ -16,7 +17,7 @@ fn packet_len_check(packet_buffer: &[u8]) -> Result<i32> {
 /// A newtype around `&[u8]` to guarantee:
 /// - Minimum one element: A packet cannot be empty.
 /// - Limited size: A packet's length may not exceed `std::i32::MAX`.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Packet<'a>(&'a [u8]);
 
 impl<'a> Packet<'a> {
@@ -24,12 +25,74 @@ impl<'a> Packet<'a> {
         self.0.as_ptr()
     }
 
+    /// Gets the packet's bytes, e.g. for logging or hashing.
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Gets the packet's length in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Always returns `false` as a packet cannot be empty.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Checks whether the packet is stereo by reading the stereo flag of
+    /// the table-of-contents byte, without calling into Opus.
+    ///
+    /// This yields the same as [`nb_channels`] but cannot fail, the packet
+    /// is not validated.
+    ///
+    /// [`nb_channels`]: fn.nb_channels.html
+    pub fn is_stereo(&self) -> bool {
+        self.0[0] & 0b100 != 0
+    }
+
     /// The underlying type is immutably borrowed and has been verified upon
     /// construction of `Packet`, thus we know casting `usize` will fit
     /// inside `i32`.
     pub fn i32_len(&self) -> i32 {
         self.0.len() as i32
     }
+
+    /// Gets the duration of each frame in the packet.
+    ///
+    /// Opus requires all frames of a packet to share the same duration, the
+    /// result is therefore the frame duration repeated for each frame.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus failed to parse the packet's frame
+    /// count.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn frame_durations(&self) -> Result<Vec<Duration>> {
+        // At 48kHz, 48 samples equal one millisecond.
+        let samples = samples_per_frame(*self, SampleRate::Hz48000)? as u64;
+        let duration = Duration::from_micros(samples * 1000 / 48);
+
+        Ok(vec![duration; nb_frames(*self)?])
+    }
+
+    /// Checks whether the packet carries `expected` channels, e.g. to detect
+    /// a mono packet within a stereo stream.
+    ///
+    /// **Errors**:
+    /// Returns [Error::InvalidChannels] with the packet's channel count when
+    /// it differs from `expected`.
+    ///
+    /// [Error::InvalidChannels]: crate::error::Error::InvalidChannels
+    pub fn expect_channels(&self, expected: Channels) -> Result<()> {
+        let channels = nb_channels(*self)?;
+
+        if channels != expected {
+            return Err(Error::InvalidChannels(channels as i32));
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a> TryFrom<&'a Vec<u8>> for Packet<'a> {
@@ -58,10 +121,40 @@ impl<'a> MutPacket<'a> {
         self.0.as_mut_ptr()
     }
 
+    /// Gets the underlying buffer's bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        self.0
+    }
+
+    /// Gets the underlying buffer's bytes mutably.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.0
+    }
+
     /// Checks if the underlying buffer meets requirements.
     pub fn i32_len(&self) -> Result<i32> {
         packet_len_check(&self.0)
     }
+
+    /// Borrows the buffer as immutable [`Packet`], e.g. to decode data
+    /// written into this packet.
+    ///
+    /// **Errors**:
+    /// Returns [Error::EmptyPacket] or [Error::PacketTooLarge] when the
+    /// buffer violates [`Packet`]'s requirements.
+    ///
+    /// [`Packet`]: struct.Packet.html
+    /// [Error::EmptyPacket]: crate::error::Error::EmptyPacket
+    /// [Error::PacketTooLarge]: crate::error::Error::PacketTooLarge
+    pub fn as_packet(&self) -> Result<Packet<'_>> {
+        Packet::try_from(&*self.0)
+    }
+
+    /// Reborrows the buffer as shorter-lived `MutPacket`, allowing to pass
+    /// it to functions taking `MutPacket` by value and use it afterwards.
+    pub fn reborrow(&mut self) -> MutPacket<'_> {
+        MutPacket(&mut *self.0)
+    }
 }
 
 impl<'a> TryFrom<&'a mut Vec<u8>> for MutPacket<'a> {
@@ -90,6 +183,11 @@ pub fn bandwidth(packet: Packet<'_>) -> Result<Bandwidth> {
 
 /// Gets number of samples per frame of an Opus `packet`.
 ///
+/// The packet only stores the frame's duration, Opus converts it into
+/// samples at the passed `sample_rate`. The result is therefore only
+/// meaningful for the sample rate the packet will be decoded at, e.g. a 20ms
+/// frame has 960 samples at 48kHz but 320 samples at 16kHz.
+///
 /// **Errors**:
 /// Empty `packet` will return `Error::EmptyPacket`.
 pub fn samples_per_frame(packet: Packet<'_>, sample_rate: SampleRate) -> Result<usize> {
@@ -139,18 +237,244 @@ pub fn nb_frames(packet: Packet<'_>) -> Result<usize> {
     }
 }
 
+/// The structure of an Opus packet as reported by [`parse`].
+///
+/// All slices borrow from the parsed packet's underlying buffer.
+///
+/// [`parse`]: fn.parse.html
+#[derive(Clone, Debug)]
+pub struct ParsedPacket<'a> {
+    toc: u8,
+    frames: Vec<&'a [u8]>,
+    payload_offset: usize,
+    padding: &'a [u8],
+}
+
+impl<'a> ParsedPacket<'a> {
+    /// Gets the table-of-contents byte of the packet.
+    pub fn toc(&self) -> u8 {
+        self.toc
+    }
+
+    /// Gets the compressed payload of all frames in the packet.
+    pub fn frames(&self) -> &[&'a [u8]] {
+        &self.frames
+    }
+
+    /// Gets the compressed payload of the frame at `index`, excluding the
+    /// table-of-contents, frame lengths, and padding.
+    pub fn frame(&self, index: usize) -> Option<&'a [u8]> {
+        self.frames.get(index).copied()
+    }
+
+    /// Gets the amount of bytes preceding the first frame's payload.
+    ///
+    /// This covers the table-of-contents byte and, depending on the packet's
+    /// code, the frame count, padding length, and frame lengths.
+    pub fn payload_offset(&self) -> usize {
+        self.payload_offset
+    }
+
+    /// Gets the padding trailing the last frame.
+    pub fn padding(&self) -> &'a [u8] {
+        self.padding
+    }
+}
+
+/// Parses an Opus `packet` into its frames without decoding them.
+///
+/// **Errors**:
+/// Returns [`Error::Opus`] when the `packet` is malformed.
+///
+/// [`Error::Opus`]: ../error/enum.Error.html#variant.Opus
+pub fn parse(packet: Packet<'_>) -> Result<ParsedPacket<'_>> {
+    let mut toc = 0;
+    let mut frame_pointers = [std::ptr::null(); 48];
+    let mut frame_sizes = [0_i16; 48];
+    let mut payload_offset = 0;
+
+    // Opus writes at most 48 frames, the maximum a packet may contain,
+    // into the arrays and reports the actual amount of frames.
+    let frame_count = try_map_opus_error(unsafe {
+        ffi::opus_packet_parse(
+            packet.as_ptr(),
+            packet.i32_len(),
+            &mut toc,
+            frame_pointers.as_mut_ptr(),
+            frame_sizes.as_mut_ptr(),
+            &mut payload_offset,
+        )
+    })? as usize;
+
+    // Opus' frame pointers point into the packet, converting them to offsets
+    // lets us borrow the frames via bounds-checked slicing.
+    let data = packet.0;
+    let mut payload_end = payload_offset as usize;
+
+    let frames = frame_pointers[..frame_count]
+        .iter()
+        .zip(&frame_sizes[..frame_count])
+        .map(|(&pointer, &size)| {
+            let start = pointer as usize - data.as_ptr() as usize;
+            payload_end = start + size as usize;
+
+            &data[start..payload_end]
+        })
+        .collect();
+
+    Ok(ParsedPacket {
+        toc,
+        frames,
+        payload_offset: payload_offset as usize,
+        padding: &data[payload_end..],
+    })
+}
+
+/// Gets the amount of padding bytes trailing the last frame of an Opus
+/// `packet`.
+///
+/// **Info**:
+/// The bytes encoding the padding's length belong to the packet's header
+/// and are not counted, padding a packet by `n` bytes therefore reports
+/// slightly less than `n`. Use [`parse`] to inspect the padding itself.
+///
+/// **Errors**:
+/// Returns [`Error::Opus`] when the `packet` is malformed.
+///
+/// [`parse`]: fn.parse.html
+/// [`Error::Opus`]: ../error/enum.Error.html#variant.Opus
+pub fn padding_len(packet: Packet<'_>) -> Result<usize> {
+    parse(packet).map(|parsed| parsed.padding().len())
+}
+
+/// Checks whether an Opus `packet` carries in-band forward error correction
+/// data, i.e. redundant low bitrate data of the previous frame.
+///
+/// Only SILK and hybrid packets can carry such data, CELT-only packets
+/// always return `false`.
+///
+/// **Info**:
+/// The SILK frame headers are range coded, this check reads the LBRR flags
+/// from the leading bits of the first frame the same way Opus' own
+/// `opus_packet_has_lbrr` does. Redundant data of later frames in the
+/// same packet is not inspected.
+///
+/// **Errors**:
+/// Returns [Error::Opus] when Opus failed to parse the `packet`.
+///
+/// [Error::Opus]: crate::error::Error::Opus
+pub fn has_fec(packet: Packet<'_>) -> Result<bool> {
+    let toc = packet.0[0];
+
+    // The highest configuration bit marks CELT-only packets.
+    if toc & 0x80 != 0 {
+        return Ok(false);
+    }
+
+    // Each 20ms SILK frame has its own voice activity and LBRR flag, they
+    // precede the per-channel LBRR flag in the bitstream.
+    let silk_frames = match samples_per_frame(packet, SampleRate::Hz48000)? {
+        samples if samples > 960 => samples / 960,
+        _ => 1,
+    };
+    let channels = nb_channels(packet)?;
+
+    let parsed = parse(packet)?;
+    // An empty frame carries no flags at all.
+    let header = parsed
+        .frame(0)
+        .and_then(|frame| frame.first())
+        .copied()
+        .unwrap_or(0);
+
+    let mid_lbrr = (header >> (7 - silk_frames)) & 1 == 1;
+    let side_lbrr = channels.is_stereo() && (header >> (6 - 2 * silk_frames)) & 1 == 1;
+
+    Ok(mid_lbrr || side_lbrr)
+}
+
+/// Writes each of the `packets` to `out`, prefixed with its length as a
+/// 2-byte big-endian integer.
+///
+/// This is a minimal framing to store several packets in one buffer, read
+/// them back with [`read_framed`].
+///
+/// **Errors**:
+/// Returns [Error::PacketTooLarge] if a packet is longer than `65535` bytes,
+/// `out` is left untouched in this case.
+///
+/// [`read_framed`]: fn.read_framed.html
+/// [Error::PacketTooLarge]: crate::error::Error::PacketTooLarge
+pub fn write_framed(packets: &[Packet<'_>], out: &mut Vec<u8>) -> Result<()> {
+    let original_len = out.len();
+    out.reserve(packets.iter().map(|packet| packet.0.len() + 2).sum());
+
+    for packet in packets {
+        let len = u16::try_from(packet.0.len()).map_err(|_| {
+            out.truncate(original_len);
+
+            Error::PacketTooLarge
+        })?;
+
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(packet.0);
+    }
+
+    Ok(())
+}
+
+/// Reads packets framed by [`write_framed`] from `data`.
+///
+/// **Errors**:
+/// Returns [Error::Opus] with [ErrorCode::InvalidPacket] if `data` ends
+/// in the middle of a length prefix or packet and [Error::EmptyPacket] if
+/// a frame has a length of zero.
+///
+/// [`write_framed`]: fn.write_framed.html
+/// [Error::Opus]: crate::error::Error::Opus
+/// [ErrorCode::InvalidPacket]: crate::error::ErrorCode::InvalidPacket
+/// [Error::EmptyPacket]: crate::error::Error::EmptyPacket
+pub fn read_framed(mut data: &[u8]) -> Result<Vec<Packet<'_>>> {
+    let mut packets = Vec::new();
+
+    while !data.is_empty() {
+        if data.len() < 2 {
+            return Err(Error::Opus(ErrorCode::InvalidPacket));
+        }
+
+        let len = usize::from(u16::from_be_bytes([data[0], data[1]]));
+        let rest = &data[2..];
+
+        if rest.len() < len {
+            return Err(Error::Opus(ErrorCode::InvalidPacket));
+        }
+
+        packets.push(Packet::try_from(&rest[..len])?);
+        data = &rest[len..];
+    }
+
+    Ok(packets)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::bandwidth;
-    use crate::{packet::Packet, Bandwidth, Error};
+    use super::{
+        bandwidth, has_fec, nb_channels, nb_frames, padding_len, parse, read_framed,
+        samples_per_frame, write_framed,
+    };
+    use crate::{
+        coder::{Decoder, Encoder},
+        packet::{MutPacket, Packet},
+        repacketizer::packet_pad,
+        Application, Bandwidth, Channels, Error, ErrorCode, FrameDuration, SampleRate,
+    };
     use matches::assert_matches;
+    use std::convert::{TryFrom, TryInto};
 
     #[test]
     /// We verify the `TryFrom`-impl for `Packet` by creating and then
     /// converting from `Vec`s that meet and violate the contract.
     fn packet_bandwidth() {
-        use std::convert::TryFrom;
-
         let empty_packet = vec![];
         let empty_packet_bandwidth = Packet::try_from(&empty_packet);
         assert_matches!(empty_packet_bandwidth, Err(Error::EmptyPacket));
@@ -175,4 +499,345 @@ mod tests {
         let fullband_bandwidth = bandwidth(Packet::try_from(&fullband_packet).unwrap());
         assert_matches!(fullband_bandwidth, Ok(Bandwidth::Fullband));
     }
+
+    #[test]
+    fn nb_frames_matches_decoded_frames() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+        let input = [0_i16; STEREO_20MS];
+        let mut packet_buffer = vec![0; 256];
+        let len = encoder.encode(&input, &mut packet_buffer).unwrap();
+        packet_buffer.truncate(len);
+
+        let packet = Packet::try_from(&packet_buffer).unwrap();
+        let frame_count = nb_frames(Packet::try_from(&packet_buffer).unwrap()).unwrap();
+        let frame_samples = samples_per_frame(packet, SampleRate::Hz48000).unwrap();
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Stereo).unwrap();
+        let mut output = vec![0_i16; STEREO_20MS];
+        let decoded_samples = decoder
+            .decode(
+                Some(Packet::try_from(&packet_buffer).unwrap()),
+                (&mut output).try_into().unwrap(),
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(frame_count, 1);
+        assert_eq!(frame_count * frame_samples, decoded_samples);
+    }
+
+    #[test]
+    fn samples_per_frame_scales_with_sample_rate() {
+        // A CELT-only fullband packet carrying one 20ms frame.
+        let packet_buffer = vec![0b1111_1000, 0];
+
+        let packet = Packet::try_from(&packet_buffer).unwrap();
+        assert_matches!(samples_per_frame(packet, SampleRate::Hz48000), Ok(960));
+        assert_matches!(samples_per_frame(packet, SampleRate::Hz16000), Ok(320));
+        assert_matches!(samples_per_frame(packet, SampleRate::Hz8000), Ok(160));
+    }
+
+    #[test]
+    fn nb_frames_of_corrupt_packet() {
+        // The TOC-byte announces an arbitrary amount of frames (code 3),
+        // but the byte carrying the frame count is missing.
+        let corrupt_packet = vec![0b0000_0011];
+
+        assert_matches!(
+            nb_frames(Packet::try_from(&corrupt_packet).unwrap()),
+            Err(Error::Opus(ErrorCode::InvalidPacket))
+        );
+    }
+
+    #[test]
+    fn parse_frames_and_padding() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = (0..MONO_20MS)
+            .map(|i| ((i * 7919) % 20000) as i16 - 10000)
+            .collect::<Vec<_>>();
+        let mut packet_buffer = vec![0; 256];
+        let len = encoder.encode(&input, &mut packet_buffer).unwrap();
+        packet_buffer.truncate(len);
+
+        let parsed = parse(Packet::try_from(&packet_buffer).unwrap()).unwrap();
+        assert_eq!(parsed.toc(), packet_buffer[0]);
+        assert_eq!(parsed.frames().len(), 1);
+        assert_eq!(parsed.frame(0), Some(&packet_buffer[1..]));
+        assert_eq!(parsed.frame(1), None);
+        assert!(parsed.padding().is_empty());
+
+        // Rebuild the packet as code 3 packet with one frame, followed by
+        // 20 bytes of padding.
+        let mut padded_buffer = vec![packet_buffer[0] | 0b11, 0b0100_0001, 20];
+        padded_buffer.extend_from_slice(&packet_buffer[1..]);
+        padded_buffer.extend_from_slice(&[0; 20]);
+
+        let parsed = parse(Packet::try_from(&padded_buffer).unwrap()).unwrap();
+        let frames_len = parsed
+            .frames()
+            .iter()
+            .map(|frame| frame.len())
+            .sum::<usize>();
+
+        assert_eq!(parsed.frame(0), Some(&packet_buffer[1..]));
+        assert_eq!(parsed.padding(), &[0; 20]);
+        assert_eq!(parsed.payload_offset(), 3);
+        assert_eq!(
+            parsed.payload_offset() + frames_len + parsed.padding().len(),
+            padded_buffer.len()
+        );
+    }
+
+    #[test]
+    fn parse_forced_120ms_packet() {
+        // 48000Hz * 1 channel * 120 ms / 1000
+        const MONO_120MS: usize = 48000 * 1 * 120 / 1000;
+
+        let mut encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        encoder
+            .set_expert_frame_duration(FrameDuration::Ms120)
+            .unwrap();
+
+        let input = (0..MONO_120MS)
+            .map(|i| ((i * 7919) % 20000) as i16 - 10000)
+            .collect::<Vec<_>>();
+        let mut packet_buffer = vec![0; 1500];
+        let len = encoder.encode(&input, &mut packet_buffer).unwrap();
+        packet_buffer.truncate(len);
+
+        let packet = Packet::try_from(&packet_buffer).unwrap();
+        let parsed = parse(packet).unwrap();
+        let frame_count = nb_frames(packet).unwrap();
+
+        assert!(frame_count > 1);
+        assert_eq!(parsed.frames().len(), frame_count);
+        assert_eq!(
+            frame_count * samples_per_frame(packet, SampleRate::Hz48000).unwrap(),
+            MONO_120MS
+        );
+
+        // Every frame borrows from the packet's buffer.
+        let buffer_range = packet_buffer.as_ptr_range();
+        assert!(parsed
+            .frames()
+            .iter()
+            .all(|frame| buffer_range.contains(&frame.as_ptr())));
+    }
+
+    #[test]
+    fn has_fec_of_encoded_packets() {
+        // 16000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 16000 * 1 * 20 / 1000;
+
+        let fec_flags = |mode, inband_fec| {
+            let mut encoder = Encoder::new(SampleRate::Hz16000, Channels::Mono, mode).unwrap();
+            encoder.set_inband_fec(inband_fec).unwrap();
+            encoder.set_packet_loss_perc(20).unwrap();
+
+            (0..10)
+                .map(|frame| {
+                    let input = (0..MONO_20MS)
+                        .map(|i| {
+                            let t = (frame * MONO_20MS + i) as f32 / 16000.0;
+                            ((2.0 * std::f32::consts::PI * 300.0 * t).sin() * 10000.0) as i16
+                        })
+                        .collect::<Vec<_>>();
+                    let mut output = vec![0; 1500];
+                    let len = encoder.encode(&input, &mut output).unwrap();
+                    output.truncate(len);
+
+                    has_fec(Packet::try_from(&output).unwrap()).unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(fec_flags(Application::Voip, true).contains(&true));
+        assert!(!fec_flags(Application::Voip, false).contains(&true));
+        assert!(!fec_flags(Application::LowDelay, true).contains(&true));
+    }
+
+    #[test]
+    fn decode_padded_mut_packet() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let mut buffer = vec![0; 256];
+        let len = encoder.encode(&[0_i16; MONO_20MS], &mut buffer).unwrap();
+        buffer.truncate(len + 20);
+
+        let mut packet = MutPacket::try_from(&mut buffer).unwrap();
+        packet_pad(packet.reborrow(), len, len + 20).unwrap();
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        let mut output = vec![0_i16; MONO_20MS];
+
+        assert_matches!(
+            decoder.decode(
+                Some(packet.as_packet().unwrap()),
+                (&mut output).try_into().unwrap(),
+                false
+            ),
+            Ok(MONO_20MS)
+        );
+        assert_eq!(
+            parse(packet.as_packet().unwrap()).unwrap().padding().len(),
+            18
+        );
+    }
+
+    #[test]
+    fn padding_len_of_padded_packet() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let mut buffer = vec![0; 256];
+        let len = encoder.encode(&[0_i16; MONO_20MS], &mut buffer).unwrap();
+        buffer.truncate(len + 20);
+
+        let mut packet = MutPacket::try_from(&mut buffer[..len]).unwrap();
+        assert_matches!(padding_len(packet.as_packet().unwrap()), Ok(0));
+        assert_matches!(nb_frames(packet.as_packet().unwrap()), Ok(1));
+
+        packet = MutPacket::try_from(&mut buffer).unwrap();
+        packet_pad(packet.reborrow(), len, len + 20).unwrap();
+
+        // Padding turns the packet into a code 3 packet, its header grows by
+        // the frame count byte and one padding length byte.
+        assert_matches!(padding_len(packet.as_packet().unwrap()), Ok(18));
+        assert_matches!(nb_frames(packet.as_packet().unwrap()), Ok(1));
+    }
+
+    #[test]
+    fn frame_durations_of_multiframe_packet() {
+        use std::time::Duration;
+
+        // 48000Hz * 1 channel * 60 ms / 1000
+        const MONO_60MS: usize = 48000 * 1 * 60 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let packet = encoder.encode_multiframe(&[0; MONO_60MS], 3, 1500).unwrap();
+
+        assert_eq!(
+            Packet::try_from(&packet)
+                .unwrap()
+                .frame_durations()
+                .unwrap(),
+            vec![Duration::from_millis(20); 3]
+        );
+
+        // Code 0 packet of a single 2.5ms CELT frame.
+        let short_packet = vec![0b1000_0000, 0];
+        assert_eq!(
+            Packet::try_from(&short_packet)
+                .unwrap()
+                .frame_durations()
+                .unwrap(),
+            vec![Duration::from_micros(2500)]
+        );
+    }
+
+    #[test]
+    fn expect_channels() {
+        // The TOC-byte's third bit signals stereo.
+        let mono_packet = vec![0b1111_1000, 0];
+        let stereo_packet = vec![0b1111_1100, 0];
+
+        let mono = Packet::try_from(&mono_packet).unwrap();
+        let stereo = Packet::try_from(&stereo_packet).unwrap();
+
+        assert_matches!(mono.expect_channels(Channels::Mono), Ok(()));
+        assert_matches!(stereo.expect_channels(Channels::Stereo), Ok(()));
+
+        assert_matches!(
+            mono.expect_channels(Channels::Stereo),
+            Err(Error::InvalidChannels(1))
+        );
+        assert_matches!(
+            stereo.expect_channels(Channels::Mono),
+            Err(Error::InvalidChannels(2))
+        );
+    }
+
+    #[test]
+    fn is_stereo_matches_nb_channels() {
+        // 48000Hz * 2 channels * 20 ms / 1000
+        const STEREO_20MS: usize = 48000 * 2 * 20 / 1000;
+
+        for &channels in &[Channels::Mono, Channels::Stereo] {
+            let encoder = Encoder::new(SampleRate::Hz48000, channels, Application::Audio).unwrap();
+            let input = &[0_i16; STEREO_20MS][..STEREO_20MS / 2 * channels as usize];
+            let packet_buffer = encoder.encode_vec(input, None).unwrap();
+            let packet = Packet::try_from(&packet_buffer).unwrap();
+
+            assert_eq!(packet.is_stereo(), nb_channels(packet).unwrap().is_stereo());
+            assert_eq!(packet.is_stereo(), channels.is_stereo());
+        }
+    }
+
+    #[test]
+    fn packet_slice_accessors() {
+        let mut buffer = vec![0xfc, 0xff, 0xfe];
+        let packet = Packet::try_from(&buffer).unwrap();
+
+        assert_eq!(packet.as_slice(), buffer.as_slice());
+        assert_eq!(packet.len(), 3);
+        assert!(!packet.is_empty());
+
+        let mut mut_packet = MutPacket::try_from(&mut buffer).unwrap();
+        mut_packet.as_mut_slice()[0] = 0xf8;
+        assert_eq!(mut_packet.as_slice(), &[0xf8, 0xff, 0xfe]);
+    }
+
+    #[test]
+    fn framed_round_trip() {
+        let first = vec![1u8, 2, 3];
+        let second = vec![4u8; 300];
+        let packets = [
+            Packet::try_from(&first).unwrap(),
+            Packet::try_from(&second).unwrap(),
+        ];
+
+        let mut framed = Vec::new();
+        write_framed(&packets, &mut framed).unwrap();
+        assert_eq!(framed.len(), 2 + 3 + 2 + 300);
+        assert_eq!(&framed[..2], &[0, 3]);
+
+        let read = read_framed(&framed).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].as_slice(), first.as_slice());
+        assert_eq!(read[1].as_slice(), second.as_slice());
+
+        assert_matches!(
+            read_framed(&framed[..framed.len() - 1]),
+            Err(Error::Opus(ErrorCode::InvalidPacket))
+        );
+        assert_matches!(read_framed(&[0, 0]), Err(Error::EmptyPacket));
+    }
+
+    #[test]
+    fn write_framed_rejects_oversized_packet() {
+        let huge = vec![0u8; usize::from(u16::MAX) + 1];
+        let packets = [Packet::try_from(&huge).unwrap()];
+
+        let mut framed = vec![7];
+        assert_matches!(
+            write_framed(&packets, &mut framed),
+            Err(Error::PacketTooLarge)
+        );
+        assert_eq!(framed, vec![7]);
+    }
 }
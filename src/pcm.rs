@@ -0,0 +1,139 @@
+//! Conversions between Opus' two signal formats, `i16` and `f32` samples.
+//!
+//! Float samples are nominally within [-1, 1], they are scaled by 32768 to
+//! match the `i16` range the same way Opus converts internally.
+//!
+//! Additionally, test tones can be generated, e.g. to feed encoders in tests.
+
+use crate::{Channels, SampleRate};
+use std::convert::TryFrom;
+
+/// The factor between a float sample and an `i16` sample.
+const SCALE: f32 = 32768.0;
+
+/// The peak amplitude of generated test tones, half of full scale.
+const SINE_AMPLITUDE: f64 = 0.5;
+
+/// Converts the float `input` into `output`, clamping samples outside of
+/// the `i16` range.
+///
+/// Only as many samples as the shorter of both slices holds are converted.
+pub fn f32_to_i16(input: &[f32], output: &mut [i16]) {
+    for (sample, converted) in input.iter().zip(output.iter_mut()) {
+        *converted = (sample * SCALE)
+            .round()
+            .max(f32::from(i16::MIN))
+            .min(f32::from(i16::MAX)) as i16;
+    }
+}
+
+/// Converts the `i16` `input` into `output`, the results lie within
+/// [-1, 1).
+///
+/// Only as many samples as the shorter of both slices holds are converted.
+pub fn i16_to_f32(input: &[i16], output: &mut [f32]) {
+    for (sample, converted) in input.iter().zip(output.iter_mut()) {
+        *converted = f32::from(*sample) / SCALE;
+    }
+}
+
+/// Generates a sine tone of `freq` Hertz lasting `samples` samples per
+/// channel, interleaved if `channels` is stereo.
+///
+/// The tone peaks at half of full scale, leaving headroom for processing.
+///
+/// **Info**:
+/// As there is no channel count to generate for, [`Channels::Auto`] yields
+/// an empty signal.
+///
+/// [`Channels::Auto`]: ../enum.Channels.html#variant.Auto
+pub fn sine(freq: f32, sample_rate: SampleRate, channels: Channels, samples: usize) -> Vec<i16> {
+    sine_float(freq, sample_rate, channels, samples)
+        .iter()
+        .map(|sample| (sample * SCALE).round() as i16)
+        .collect()
+}
+
+/// Generates a float sine tone like [`sine`], the samples lie within
+/// [-0.5, 0.5].
+///
+/// [`sine`]: fn.sine.html
+pub fn sine_float(
+    freq: f32,
+    sample_rate: SampleRate,
+    channels: Channels,
+    samples: usize,
+) -> Vec<f32> {
+    let channels = usize::try_from(channels as i32).unwrap_or(0);
+    let step = 2.0 * std::f64::consts::PI * f64::from(freq) / f64::from(sample_rate as i32);
+
+    (0..samples * channels)
+        .map(|i| (SINE_AMPLITUDE * (step * (i / channels) as f64).sin()) as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{f32_to_i16, i16_to_f32, sine, sine_float};
+    use crate::{Channels, SampleRate};
+
+    #[test]
+    fn sine_period() {
+        // A 1kHz tone repeats every 48 samples at 48kHz.
+        const PERIOD: usize = 48;
+
+        let tone = sine(1000.0, SampleRate::Hz48000, Channels::Mono, 480);
+        assert_eq!(tone.len(), 480);
+        assert!(tone
+            .iter()
+            .zip(&tone[PERIOD..])
+            .all(|(sample, repeated)| (sample - repeated).abs() <= 1));
+
+        let rising_zero_crossings = tone
+            .windows(2)
+            .filter(|pair| pair[0] < 0 && pair[1] >= 0)
+            .count();
+        assert_eq!(rising_zero_crossings, 480 / PERIOD - 1);
+
+        let peak = tone.iter().map(|sample| sample.abs()).max().unwrap();
+        assert_eq!(peak, 16384);
+    }
+
+    #[test]
+    fn sine_channels() {
+        let stereo = sine_float(440.0, SampleRate::Hz16000, Channels::Stereo, 160);
+        assert_eq!(stereo.len(), 320);
+        assert!(stereo
+            .chunks(2)
+            .all(|frame| (frame[0] - frame[1]).abs() < f32::EPSILON));
+
+        assert!(sine(440.0, SampleRate::Hz16000, Channels::Auto, 160).is_empty());
+    }
+
+    #[test]
+    fn float_to_int() {
+        let mut output = [0_i16; 5];
+        f32_to_i16(&[1.0, -1.0, 2.0, -2.0, 0.5], &mut output);
+
+        assert_eq!(output, [32767, -32768, 32767, -32768, 16384]);
+    }
+
+    #[test]
+    fn int_to_float() {
+        let mut output = [0_f32; 3];
+        i16_to_f32(&[-32768, 0, 16384], &mut output);
+
+        assert!(output
+            .iter()
+            .zip(&[-1.0, 0.0, 0.5])
+            .all(|(converted, expected)| (converted - expected).abs() < f32::EPSILON));
+    }
+
+    #[test]
+    fn convert_shorter_length() {
+        let mut output = [7_i16; 3];
+        f32_to_i16(&[0.5], &mut output);
+
+        assert_eq!(output, [16384, 7, 7]);
+    }
+}
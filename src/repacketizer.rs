@@ -1,24 +1,38 @@
 use crate::{
+    coder::max_packet_bytes,
     error::try_map_opus_error,
     ffi,
     packet::{MutPacket, Packet},
-    Result,
+    Error, ErrorCode, Result,
 };
+use std::{convert::TryFrom, marker::PhantomData};
 
 /// Returns Opus' internal `OpusRepacketizer`'s size in bytes.
 pub fn repacketizer_size() -> usize {
     unsafe { ffi::opus_repacketizer_get_size() as usize }
 }
 
+/// Pads the multistream packet of `nb_streams` streams occupying the first
+/// `len` bytes of `data` to `new_len` bytes.
+///
+/// **Errors**:
+/// Returns [Error::Opus] with [ErrorCode::BufferTooSmall] when `data` is
+/// shorter than `len` or `new_len`.
+///
+/// [Error::Opus]: crate::error::Error::Opus
+/// [ErrorCode::BufferTooSmall]: crate::error::ErrorCode::BufferTooSmall
 pub fn multistream_packet_pad(
     mut data: MutPacket<'_>,
+    len: usize,
     new_len: usize,
     nb_streams: usize,
 ) -> Result<()> {
+    check_pad_capacity(&data, len, new_len)?;
+
     try_map_opus_error(unsafe {
         ffi::opus_multistream_packet_pad(
             data.as_mut_ptr(),
-            data.i32_len()?,
+            len as i32,
             new_len as i32,
             nb_streams as i32,
         )
@@ -33,9 +47,33 @@ pub fn multistream_packet_unpad(mut data: MutPacket<'_>, nb_streams: usize) -> R
     .map(|_| ())
 }
 
-pub fn packet_pad(mut data: MutPacket<'_>, new_len: i32) -> Result<()> {
-    try_map_opus_error(unsafe { ffi::opus_packet_pad(data.as_mut_ptr(), data.i32_len()?, new_len) })
-        .map(|_| ())
+/// Pads the packet occupying the first `len` bytes of `data` to `new_len`
+/// bytes.
+///
+/// **Errors**:
+/// Returns [Error::Opus] with [ErrorCode::BufferTooSmall] when `data` is
+/// shorter than `len` or `new_len`.
+///
+/// [Error::Opus]: crate::error::Error::Opus
+/// [ErrorCode::BufferTooSmall]: crate::error::ErrorCode::BufferTooSmall
+pub fn packet_pad(mut data: MutPacket<'_>, len: usize, new_len: usize) -> Result<()> {
+    check_pad_capacity(&data, len, new_len)?;
+
+    try_map_opus_error(unsafe {
+        ffi::opus_packet_pad(data.as_mut_ptr(), len as i32, new_len as i32)
+    })
+    .map(|_| ())
+}
+
+/// Opus reads `len` and writes `new_len` bytes, both must fit into `data`.
+fn check_pad_capacity(data: &MutPacket<'_>, len: usize, new_len: usize) -> Result<()> {
+    let capacity = data.i32_len()? as usize;
+
+    if len > capacity || new_len > capacity {
+        return Err(Error::Opus(ErrorCode::BufferTooSmall));
+    }
+
+    Ok(())
 }
 
 pub fn packet_unpad(mut data: MutPacket<'_>) -> Result<()> {
@@ -73,20 +111,113 @@ impl Repacketizer {
         unsafe { ffi::opus_repacketizer_get_nb_frames(self.pointer) as usize }
     }
 
-    pub fn repacketizer_out(&self, mut data_out: MutPacket<'_>, max_len: i32) -> Result<()> {
+    /// Resets the repacketizer, discarding all catted frames, to reuse its
+    /// allocation for the next batch of packets.
+    ///
+    /// Catting starts fresh afterwards, the next packet may use a different
+    /// configuration than the discarded frames.
+    pub fn reset(&mut self) -> &mut Self {
+        unsafe { ffi::opus_repacketizer_init(self.pointer) };
+
+        self
+    }
+
+    /// Resets the repacketizer and starts collecting frames of packets that
+    /// live at least as long as the returned state.
+    ///
+    /// Unlike [`repacketizer_cat`], the state borrows the catted packets,
+    /// the borrow checker ensures their buffers stay alive until the frames
+    /// have been emitted.
+    ///
+    /// [`repacketizer_cat`]: struct.Repacketizer.html#method.repacketizer_cat
+    pub fn begin<'p>(&mut self) -> RepacketizerState<'_, 'p> {
+        RepacketizerState {
+            repacketizer: self.reset(),
+            packets: PhantomData,
+        }
+    }
+
+    /// Emits all catted frames as one packet into `data_out` and returns the
+    /// packet's length.
+    pub fn repacketizer_out(&self, mut data_out: MutPacket<'_>, max_len: i32) -> Result<usize> {
         try_map_opus_error(unsafe {
             ffi::opus_repacketizer_out(self.pointer, data_out.as_mut_ptr(), max_len)
         })
-        .map(|_| ())
+        .map(|n| n as usize)
+    }
+
+    /// Emits all catted frames as one newly allocated packet of at most
+    /// `max_len` bytes.
+    ///
+    /// The packet is emitted into a buffer fitting any packet of the catted
+    /// frame count first, so a too small `max_len` reports the exact length
+    /// needed instead of failing inside Opus.
+    ///
+    /// **Errors**:
+    /// Returns [Error::OutputTooSmall] with the combined packet's length when
+    /// it exceeds `max_len`.
+    ///
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g. no frames
+    /// having been catted.
+    ///
+    /// [Error::OutputTooSmall]: crate::error::Error::OutputTooSmall
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn combine(&self, max_len: usize) -> Result<Vec<u8>> {
+        let frames = u8::try_from(self.nb_frames()).unwrap_or(u8::MAX);
+        let mut output = vec![0; max_packet_bytes(frames).max(1)];
+        let output_packet = MutPacket::try_from(&mut output)?;
+        let output_len = output_packet.i32_len()?;
+        let needed = self.repacketizer_out(output_packet, output_len)?;
+
+        if needed > max_len {
+            return Err(Error::OutputTooSmall {
+                needed,
+                got: max_len,
+            });
+        }
+
+        output.truncate(needed);
+
+        Ok(output)
     }
 
+    /// Merges the frames of all `packets`, in order, into one packet written
+    /// to `out` and returns the packet's length.
+    ///
+    /// The repacketizer is [`reset`] first, previously catted frames are
+    /// discarded.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] with [ErrorCode::InvalidPacket] when the
+    /// packets' configurations differ or their frames exceed 120ms.
+    ///
+    /// Returns [Error::Opus] with [ErrorCode::BufferTooSmall] when the
+    /// merged packet does not fit into `out`.
+    ///
+    /// [`reset`]: struct.Repacketizer.html#method.reset
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [ErrorCode::InvalidPacket]: crate::error::ErrorCode::InvalidPacket
+    /// [ErrorCode::BufferTooSmall]: crate::error::ErrorCode::BufferTooSmall
+    pub fn combine_into(&mut self, packets: &[Packet<'_>], out: &mut [u8]) -> Result<usize> {
+        let state = packets
+            .iter()
+            .try_fold(self.begin(), |state, &packet| state.cat(packet))?;
+
+        let mut out_packet = MutPacket::try_from(out)?;
+        let max_len = out_packet.i32_len()?;
+
+        state.out(&mut out_packet, max_len)
+    }
+
+    /// Emits the catted frames from `begin` up to excluding `end` as one
+    /// packet into `data_out` and returns the packet's length.
     pub fn repacketizer_out_range(
         &self,
         begin: i32,
         end: i32,
         mut data_out: MutPacket<'_>,
         max_len: i32,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         try_map_opus_error(unsafe {
             ffi::opus_repacketizer_out_range(
                 self.pointer,
@@ -96,9 +227,27 @@ impl Repacketizer {
                 max_len,
             )
         })
-        .map(|_| ())
+        .map(|n| n as usize)
     }
 
+    /// Adds the frames of the packet `data` to the repacketizer's state.
+    ///
+    /// **Warning**:
+    /// Opus does not copy the frames, it only keeps pointers into `data`'s
+    /// underlying buffer. The buffer must stay alive and unchanged until the
+    /// frames have been emitted via [`repacketizer_out`] or
+    /// [`repacketizer_out_range`]. Dropping or reusing the buffer before
+    /// that makes Opus read freed or overwritten memory.
+    /// Use [`begin`] instead to have the borrow checker enforce this.
+    ///
+    /// **Info**:
+    /// Padding of `data` is accepted and dropped, only the frames are kept.
+    /// The emitted packet carries no padding and decodes identically to
+    /// its unpadded source packets.
+    ///
+    /// [`begin`]: struct.Repacketizer.html#method.begin
+    /// [`repacketizer_out`]: struct.Repacketizer.html#method.repacketizer_out
+    /// [`repacketizer_out_range`]: struct.Repacketizer.html#method.repacketizer_out_range
     pub fn repacketizer_cat(&self, data: Packet<'_>) -> Result<()> {
         try_map_opus_error(unsafe {
             ffi::opus_repacketizer_cat(self.pointer, data.as_ptr(), data.i32_len())
@@ -106,3 +255,324 @@ impl Repacketizer {
         .map(|_| ())
     }
 }
+
+/// A freshly initialised [`Repacketizer`] collecting frames of packets
+/// borrowed for `'p`, created by [`Repacketizer::begin`].
+///
+/// [`Repacketizer`]: struct.Repacketizer.html
+/// [`Repacketizer::begin`]: struct.Repacketizer.html#method.begin
+#[derive(Debug)]
+pub struct RepacketizerState<'r, 'p> {
+    repacketizer: &'r mut Repacketizer,
+    packets: PhantomData<Packet<'p>>,
+}
+
+impl<'p> RepacketizerState<'_, 'p> {
+    /// Adds the frames of `packet` to the state.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] with [ErrorCode::InvalidPacket] when `packet`
+    /// is malformed, its configuration differs from the previously added
+    /// packets, or the frames would exceed 120ms.
+    ///
+    /// The packet's buffer has to outlive the state, dropping it before the
+    /// frames have been emitted is rejected:
+    ///
+    /// ```compile_fail
+    /// use audiopus::{
+    ///     packet::{MutPacket, Packet},
+    ///     repacketizer::Repacketizer,
+    /// };
+    /// use std::convert::TryFrom;
+    ///
+    /// let mut repacketizer = Repacketizer::new();
+    /// let mut output = vec![0; 1500];
+    /// let mut output_packet = MutPacket::try_from(&mut output).unwrap();
+    ///
+    /// let buffer = vec![0b1111_1000, 0xff, 0xfe];
+    /// let state = repacketizer
+    ///     .begin()
+    ///     .cat(Packet::try_from(&buffer).unwrap())
+    ///     .unwrap();
+    /// drop(buffer);
+    ///
+    /// state.out(&mut output_packet, 1500).unwrap();
+    /// ```
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    /// [ErrorCode::InvalidPacket]: crate::error::ErrorCode::InvalidPacket
+    pub fn cat(self, packet: Packet<'p>) -> Result<Self> {
+        self.repacketizer.repacketizer_cat(packet)?;
+
+        Ok(self)
+    }
+
+    /// Gets the number of frames added so far.
+    pub fn nb_frames(&self) -> usize {
+        self.repacketizer.nb_frames()
+    }
+
+    /// Emits all added frames as one packet into `data_out` and returns the
+    /// packet's length.
+    ///
+    /// **Errors**:
+    /// Returns [Error::Opus] when Opus encountered a problem, e.g. no frames
+    /// having been added or `max_len` being too small.
+    ///
+    /// [Error::Opus]: crate::error::Error::Opus
+    pub fn out(&self, data_out: &mut MutPacket<'_>, max_len: i32) -> Result<usize> {
+        self.repacketizer
+            .repacketizer_out(data_out.reborrow(), max_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{packet_pad, Repacketizer};
+    use crate::{
+        coder::{Decoder, Encoder},
+        packet::{nb_frames, MutPacket, Packet},
+        pcm, Application, Channels, Error, ErrorCode, MutSignals, SampleRate,
+    };
+    use matches::assert_matches;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn cat_with_buffers_alive() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = [0_i16; MONO_20MS];
+
+        let mut first = vec![0; 256];
+        let len = encoder.encode(&input, &mut first).unwrap();
+        first.truncate(len);
+
+        let mut second = vec![0; 256];
+        let len = encoder.encode(&input, &mut second).unwrap();
+        second.truncate(len);
+
+        let repacketizer = Repacketizer::new();
+        repacketizer
+            .repacketizer_cat(Packet::try_from(&first).unwrap())
+            .unwrap();
+        repacketizer
+            .repacketizer_cat(Packet::try_from(&second).unwrap())
+            .unwrap();
+        assert_eq!(repacketizer.nb_frames(), 2);
+
+        // Both source buffers are still alive while emitting the frames.
+        let mut output = vec![0; 512];
+        let len = repacketizer
+            .repacketizer_out(MutPacket::try_from(&mut output).unwrap(), 512)
+            .unwrap();
+        output.truncate(len);
+
+        assert_eq!(nb_frames(Packet::try_from(&output).unwrap()).unwrap(), 2);
+    }
+
+    #[test]
+    fn combine_into_sixty_ms_packet() {
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+        let signal = pcm::sine(440.0, SampleRate::Hz48000, Channels::Mono, MONO_20MS * 3);
+        let packet_buffers = signal
+            .chunks(MONO_20MS)
+            .map(|frame| encoder.encode_vec(frame, None).unwrap())
+            .collect::<Vec<_>>();
+        let packets = packet_buffers
+            .iter()
+            .map(|packet_buffer| Packet::try_from(packet_buffer).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut repacketizer = Repacketizer::new();
+        let mut output = vec![0; 1500];
+        let len = repacketizer.combine_into(&packets, &mut output).unwrap();
+        output.truncate(len);
+
+        let combined = Packet::try_from(&output).unwrap();
+        assert_eq!(nb_frames(combined).unwrap(), 3);
+
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        let mut signals = vec![0_i16; MONO_20MS * 3];
+        assert_matches!(
+            decoder.decode(
+                Some(combined),
+                MutSignals::try_from(&mut signals).unwrap(),
+                false
+            ),
+            Ok(2880)
+        );
+
+        let stereo_encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+        let stereo_buffer = stereo_encoder
+            .encode_vec(&[0_i16; MONO_20MS * 2], None)
+            .unwrap();
+        let mixed = [packets[0], Packet::try_from(&stereo_buffer).unwrap()];
+
+        assert_matches!(
+            repacketizer.combine_into(&mixed, &mut [0; 1500]),
+            Err(Error::Opus(ErrorCode::InvalidPacket))
+        );
+    }
+
+    #[test]
+    fn reset_restarts_frame_count() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let packet_buffer = encoder.encode_vec(&[0_i16; MONO_20MS], None).unwrap();
+        let packet = Packet::try_from(&packet_buffer).unwrap();
+
+        let mut repacketizer = Repacketizer::new();
+        repacketizer.repacketizer_cat(packet).unwrap();
+        repacketizer.repacketizer_cat(packet).unwrap();
+        assert_eq!(repacketizer.nb_frames(), 2);
+
+        assert_eq!(repacketizer.reset().nb_frames(), 0);
+
+        repacketizer.repacketizer_cat(packet).unwrap();
+        assert_eq!(repacketizer.nb_frames(), 1);
+    }
+
+    #[test]
+    fn state_joins_two_packets() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = [0_i16; MONO_20MS];
+
+        let first = encoder.encode_vec(&input, None).unwrap();
+        let second = encoder.encode_vec(&input, None).unwrap();
+
+        let mut repacketizer = Repacketizer::new();
+        repacketizer
+            .repacketizer_cat(Packet::try_from(&first).unwrap())
+            .unwrap();
+
+        // Beginning discards the frames catted before.
+        let state = repacketizer
+            .begin()
+            .cat(Packet::try_from(&first).unwrap())
+            .unwrap()
+            .cat(Packet::try_from(&second).unwrap())
+            .unwrap();
+        assert_eq!(state.nb_frames(), 2);
+
+        let mut output = vec![0; 512];
+        let mut output_packet = MutPacket::try_from(&mut output).unwrap();
+        let len = state.out(&mut output_packet, 512).unwrap();
+        output.truncate(len);
+
+        assert_eq!(nb_frames(Packet::try_from(&output).unwrap()).unwrap(), 2);
+    }
+
+    #[test]
+    fn combine_reports_needed_len() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = (0..MONO_20MS)
+            .map(|i| ((i * 7919) % 20000) as i16 - 10000)
+            .collect::<Vec<_>>();
+
+        let first = encoder.encode_vec(&input, None).unwrap();
+        let second = encoder.encode_vec(&input, None).unwrap();
+
+        let repacketizer = Repacketizer::new();
+        assert_matches!(
+            repacketizer.combine(1500),
+            Err(Error::Opus(ErrorCode::BadArgument))
+        );
+
+        repacketizer
+            .repacketizer_cat(Packet::try_from(&first).unwrap())
+            .unwrap();
+        repacketizer
+            .repacketizer_cat(Packet::try_from(&second).unwrap())
+            .unwrap();
+
+        let combined = repacketizer.combine(1500).unwrap();
+        assert_eq!(nb_frames(Packet::try_from(&combined).unwrap()).unwrap(), 2);
+
+        let too_small = first.len();
+        assert_matches!(
+            repacketizer.combine(too_small),
+            Err(Error::OutputTooSmall { needed, got })
+                if needed == combined.len() && got == too_small
+        );
+    }
+
+    #[test]
+    fn cat_strips_padding() {
+        let encoder =
+            Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio).unwrap();
+
+        // 48000Hz * 1 channel * 20 ms / 1000
+        const MONO_20MS: usize = 48000 * 1 * 20 / 1000;
+        let input = (0..MONO_20MS)
+            .map(|i| ((i * 7919) % 20000) as i16 - 10000)
+            .collect::<Vec<_>>();
+
+        let mut original = vec![0; 256];
+        let len = encoder.encode(&input, &mut original).unwrap();
+        original.truncate(len);
+
+        let mut padded = original.clone();
+        padded.resize(len + 50, 0);
+        packet_pad(MutPacket::try_from(&mut padded).unwrap(), len, len + 50).unwrap();
+        assert_ne!(padded[..len], original[..]);
+
+        let repacketizer = Repacketizer::new();
+        repacketizer
+            .repacketizer_cat(Packet::try_from(&padded).unwrap())
+            .unwrap();
+
+        let mut output = vec![0; 512];
+        let out_len = repacketizer
+            .repacketizer_out(MutPacket::try_from(&mut output).unwrap(), 512)
+            .unwrap();
+        output.truncate(out_len);
+
+        assert_eq!(output, original);
+
+        let decode = |packet: &Vec<u8>| {
+            let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+            let mut signals = vec![0_i16; MONO_20MS];
+            decoder
+                .decode(
+                    Some(Packet::try_from(packet).unwrap()),
+                    MutSignals::try_from(&mut signals).unwrap(),
+                    false,
+                )
+                .unwrap();
+
+            signals
+        };
+
+        assert_eq!(decode(&output), decode(&original));
+        assert_eq!(decode(&padded), decode(&original));
+    }
+
+    #[test]
+    fn pad_beyond_buffer() {
+        let mut packet = vec![0b1111_1000, 0xff, 0xfe];
+
+        assert_matches!(
+            packet_pad(MutPacket::try_from(&mut packet).unwrap(), 3, 10),
+            Err(Error::Opus(ErrorCode::BufferTooSmall))
+        );
+    }
+}
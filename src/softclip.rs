@@ -1,6 +1,6 @@
 use crate::{ffi, Channels, MutSignals, Result};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SoftClip {
     channels: Channels,
     memory: [f32; 2],
@@ -14,6 +14,15 @@ impl SoftClip {
         }
     }
 
+    /// Clears the memory carried over from previously clipped signals.
+    ///
+    /// This should be done whenever the associated decoder is reset,
+    /// otherwise the first signals after the reset are clipped based on the
+    /// previous stream.
+    pub fn reset(&mut self) {
+        self.memory = [0.0; 2];
+    }
+
     /// Opus applies soft-clipping to bring a f32 signal within the
     /// [-1,1] range.
     pub fn apply(&mut self, mut signals: MutSignals<'_, f32>) -> Result<()> {